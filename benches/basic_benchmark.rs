@@ -7,13 +7,13 @@ use rand::{thread_rng, Rng};
 use ray_tracer::object::aabb::AABB;
 use ray_tracer::object::texture::SolidColor;
 use ray_tracer::object::{make_material, make_sphere, LambertianDiffuse};
-use ray_tracer::render::Camera;
+use ray_tracer::render::camera::PerspectiveCamera;
 use ray_tracer::utils::Vec3;
 
 /// camera benchmark
 fn camera_benchmark(c: &mut Criterion) {
     c.bench_function("Get random point in unit disk", |b| {
-        b.iter(|| Camera::get_rand_in_unit_disk());
+        b.iter(PerspectiveCamera::get_rand_in_unit_disk);
     });
 }
 
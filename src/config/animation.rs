@@ -0,0 +1,100 @@
+//! configuration for a short animation: a tumbling cube hopping around, filmed by a moving camera
+
+use std::sync::Arc;
+
+use crate::config::SceneConfig;
+use crate::object::animated::AnimatedTransform;
+use crate::object::cube::Cube;
+use crate::object::material::DiffuseLight;
+use crate::object::texture::{CheckerTexture, SolidColor};
+use crate::object::{make_hittable, make_material, make_sphere, LambertianDiffuse, Metal, World};
+use crate::render::camera::{make_camera, CameraObject};
+use crate::render::camera_path::CameraPath;
+use crate::render::skybox::{ColorGradientSkyBox, SkyBox};
+use crate::utils::keyframe::{Interpolation, Keyframes};
+use crate::utils::{Color, Vec3};
+
+pub struct AnimationScene {}
+
+impl SceneConfig for AnimationScene {
+    // orbit half way around the scene in 4 seconds, slowly zooming in
+    fn get_camera(&self) -> CameraObject {
+        let origin = Keyframes::new(0.0, Vec3::new(10.0, 3.0, 0.0))
+            .add(0.0, Vec3::new(10.0, 3.0, 0.0), Interpolation::CatmullRom)
+            .add(1.0, Vec3::new(7.0, 3.5, 7.0), Interpolation::CatmullRom)
+            .add(2.0, Vec3::new(0.0, 4.0, 10.0), Interpolation::CatmullRom)
+            .add(3.0, Vec3::new(-7.0, 3.5, 7.0), Interpolation::CatmullRom)
+            .add(4.0, Vec3::new(-10.0, 3.0, 0.0), Interpolation::Linear);
+        let look_at = Keyframes::constant(Vec3::new(0.0, 1.0, 0.0));
+        let vfov = Keyframes::new(0.0, 40.0).add(4.0, 30.0, Interpolation::Linear);
+        make_camera(CameraPath::new(
+            origin,
+            look_at,
+            vfov,
+            Vec3::new(0.0, 1.0, 0.0),
+            1.5,
+            0.0,
+        ))
+    }
+
+    fn get_world(&self) -> World {
+        let mut world = World::new();
+        let sb: Arc<dyn SkyBox + Send + Sync> = Arc::new(ColorGradientSkyBox {
+            v1: Color::new(1.0, 1.0, 1.0),
+            v2: Color::new(0.5, 0.7, 1.0),
+        });
+        world.set_skybox(&sb);
+        let ground = make_material(LambertianDiffuse {
+            texture: Arc::new(CheckerTexture {
+                odd_color: Arc::new(SolidColor::new(0.9, 0.9, 0.9)),
+                even_color: Arc::new(SolidColor::new(0.2, 0.3, 0.1)),
+            }),
+        });
+        world.add_hittable(&make_sphere(Vec3::new(0.0, -1000.0, 0.0), 1000.0, &ground));
+        let metal = make_material(Metal {
+            fuzziness: 0.05,
+            albedo: Vec3::new(0.8, 0.8, 0.9),
+        });
+        world.add_hittable(&make_sphere(Vec3::new(0.0, 1.0, 0.0), 1.0, &metal));
+
+        // a unit cube centered at the origin, hopping around the ball while tumbling
+        let red = make_material(LambertianDiffuse {
+            texture: Arc::new(SolidColor::new(0.8, 0.1, 0.1)),
+        });
+        let cube = make_hittable(Cube::new(
+            Vec3::new(-0.5, -0.5, -0.5),
+            Vec3::new(0.5, 0.5, 0.5),
+            &red,
+        ));
+        let mut translation = Keyframes::new(0.0, Vec3::new(3.0, 0.5, 0.0));
+        for i in 1..=8 {
+            let angle = i as f64 * std::f64::consts::PI / 4.0;
+            let height = if i % 2 == 1 { 2.0 } else { 0.5 };
+            translation = translation.add(
+                i as f64 * 0.5,
+                Vec3::new(3.0 * angle.cos(), height, -3.0 * angle.sin()),
+                Interpolation::CatmullRom,
+            );
+        }
+        let rotation = Keyframes::new(0.0, Vec3::zero())
+            .add(0.0, Vec3::zero(), Interpolation::Ease)
+            .add(4.0, Vec3::new(360.0, 720.0, 0.0), Interpolation::Ease);
+        let scale = Keyframes::new(0.0, 1.0)
+            .add(2.0, 1.5, Interpolation::Ease)
+            .add(4.0, 1.0, Interpolation::Ease);
+        world.add_hittable(&make_hittable(AnimatedTransform::new(
+            &cube,
+            translation,
+            rotation,
+            scale,
+        )));
+
+        let light = make_material(DiffuseLight {
+            emit: Arc::new(SolidColor::new(1.0, 0.9, 0.7)),
+            brightness: 4.0,
+        });
+        world.add_hittable(&make_sphere(Vec3::new(0.0, 6.0, 0.0), 1.0, &light));
+        world.update_metadata();
+        world
+    }
+}
@@ -0,0 +1,92 @@
+//! configuration for a night street of out-of-focus lights, showing the bokeh of the aperture
+
+use std::sync::Arc;
+
+use rand::prelude::StdRng;
+use rand::{Rng, SeedableRng};
+
+use crate::config::SceneConfig;
+use crate::object::material::{Dielectric, DiffuseLight};
+use crate::object::texture::{CheckerTexture, SolidColor};
+use crate::object::{make_material, make_sphere, LambertianDiffuse, World};
+use crate::render::camera::{make_camera, CameraObject, PerspectiveCamera};
+use crate::render::lens::ApertureShape;
+use crate::render::skybox::{ColorGradientSkyBox, SkyBox};
+use crate::utils::{Color, Vec3};
+
+pub struct BokehScene {}
+
+impl SceneConfig for BokehScene {
+    // focus on the glass ball, with a wide hexagonal aperture
+    fn get_camera(&self) -> CameraObject {
+        let look_from = Vec3::new(0.0, 1.0, 6.0);
+        let look_at = Vec3::new(0.0, 0.8, 0.0);
+        let mut camera = PerspectiveCamera::look_from(
+            look_from,
+            look_at,
+            Vec3::new(0.0, 1.0, 0.0),
+            30.0,
+            1.5,
+            0.6,
+            (look_at - look_from).length(),
+            0.0,
+            0.01,
+        );
+        camera.set_aperture_shape(ApertureShape::polygon(6, 90.0));
+        make_camera(camera)
+    }
+
+    fn get_world(&self) -> World {
+        let mut world = World::new();
+        let skybox: Arc<dyn SkyBox + Send + Sync> = Arc::new(ColorGradientSkyBox {
+            v1: Color::new(0.0, 0.0, 0.02),
+            v2: Color::zero(),
+        });
+        world.set_skybox(&skybox);
+        let mat_ground = make_material(LambertianDiffuse {
+            texture: Arc::new(CheckerTexture {
+                odd_color: Arc::new(SolidColor::new(0.3, 0.3, 0.3)),
+                even_color: Arc::new(SolidColor::new(0.1, 0.1, 0.1)),
+            }),
+        });
+        world.add_hittable(&make_sphere(
+            Vec3::new(0.0, -1000.0, 0.0),
+            1000.0,
+            &mat_ground,
+        ));
+
+        // the subject in focus, lit by a lamp above
+        let glass = make_material(Dielectric::new(1.5, Vec3::one()));
+        world.add_hittable(&make_sphere(Vec3::new(0.0, 0.8, 0.0), 0.8, &glass));
+        let diffuse = make_material(LambertianDiffuse {
+            texture: Arc::new(SolidColor::new(0.8, 0.3, 0.1)),
+        });
+        world.add_hittable(&make_sphere(Vec3::new(1.3, 0.4, 0.5), 0.4, &diffuse));
+        let lamp = make_material(DiffuseLight {
+            emit: Arc::new(SolidColor::new(1.0, 0.9, 0.8)),
+            brightness: 8.0,
+        });
+        world.add_hittable(&make_sphere(Vec3::new(-1.0, 4.0, 2.0), 0.5, &lamp));
+
+        // small colored lights far behind, blurred into the shape of the aperture
+        let mut rng = StdRng::seed_from_u64(20200707);
+        for _ in 0..60 {
+            let center = Vec3::new(
+                rng.gen_range(-12.0, 12.0),
+                rng.gen_range(0.5, 6.0),
+                rng.gen_range(-30.0, -15.0),
+            );
+            let light = make_material(DiffuseLight {
+                emit: Arc::new(SolidColor::new(
+                    rng.gen_range(0.5, 1.0),
+                    rng.gen_range(0.3, 0.9),
+                    rng.gen_range(0.1, 0.6),
+                )),
+                brightness: 20.0,
+            });
+            world.add_hittable(&make_sphere(center, 0.08, &light));
+        }
+        world.update_metadata();
+        world
+    }
+}
@@ -0,0 +1,105 @@
+//! configuration for surfaces with normal & bump maps in front of a brick wall
+
+use std::sync::Arc;
+
+use crate::config::SceneConfig;
+use crate::io::file::read_picture;
+use crate::object::material::{BumpMap, NormalMap};
+use crate::object::plane::Plane;
+use crate::object::texture::{ImageTexture, MarbleTexture};
+use crate::object::{
+    make_hittable, make_material, make_sphere, make_texture, LambertianDiffuse, Metal,
+    NoiseTexture, SolidColor, World,
+};
+use crate::render::camera::{make_camera, CameraObject, PerspectiveCamera};
+use crate::render::skybox::{ColorGradientSkyBox, SkyBox};
+use crate::utils::perlin::Perlin;
+use crate::utils::{Color, Vec3};
+
+pub struct BumpScene {}
+
+impl SceneConfig for BumpScene {
+    fn get_camera(&self) -> CameraObject {
+        let look_from = Vec3::new(5.0, 2.5, 12.0);
+        let look_at = Vec3::new(5.0, 1.2, 3.0);
+        make_camera(PerspectiveCamera::look_from(
+            look_from,
+            look_at,
+            Vec3::new(0.0, 1.0, 0.0),
+            35.0,
+            1.5,
+            0.0,
+            (look_at - look_from).length(),
+            0.0,
+            0.01,
+        ))
+    }
+
+    fn get_world(&self) -> World {
+        let mut world = World::new();
+        let sb: Arc<dyn SkyBox + Send + Sync> = Arc::new(ColorGradientSkyBox {
+            v1: Color::new(1.0, 1.0, 1.0),
+            v2: Color::new(0.5, 0.7, 1.0),
+        });
+        world.set_skybox(&sb);
+        let diffuse = |r, g, b| {
+            make_material(LambertianDiffuse {
+                texture: make_texture(SolidColor::new(r, g, b)),
+            })
+        };
+        let bricks = make_texture(ImageTexture::new(Arc::new(read_picture(
+            "assets/textures/bricks_normal.png",
+        ))));
+
+        world.add_hittable(&make_hittable(Plane::new(
+            Vec3::zero(),
+            Vec3::new(0.0, 1.0, 0.0),
+            &diffuse(0.5, 0.5, 0.5),
+        )));
+        // a wall of bricks, each texture tile one unit wide
+        let wall = make_material(NormalMap {
+            material: diffuse(0.6, 0.25, 0.15),
+            map: bricks.clone(),
+        });
+        world.add_hittable(&make_hittable(Plane::new(
+            Vec3::zero(),
+            Vec3::new(0.0, 0.0, 1.0),
+            &wall,
+        )));
+
+        // noise & marble bumps, from solid textures of the position
+        let perlin = Arc::new(Perlin::new());
+        let bumpy = make_material(BumpMap {
+            material: diffuse(0.9, 0.9, 0.8),
+            height: make_texture(NoiseTexture {
+                generator: perlin.clone(),
+                frequency: 4.0,
+                shifted: true,
+            }),
+            scale: 0.05,
+        });
+        world.add_hittable(&make_sphere(Vec3::new(2.5, 1.0, 3.0), 1.0, &bumpy));
+        let grooved = make_material(BumpMap {
+            material: make_material(Metal {
+                fuzziness: 0.1,
+                albedo: Color::new(0.9, 0.8, 0.6),
+            }),
+            height: make_texture(MarbleTexture {
+                generator: perlin,
+                scale: 8.0,
+                turbulence: 4.0,
+            }),
+            scale: 0.05,
+        });
+        world.add_hittable(&make_sphere(Vec3::new(5.0, 1.0, 3.0), 1.0, &grooved));
+        // a ball of bricks from the normal map
+        let ball = make_material(NormalMap {
+            material: diffuse(0.8, 0.8, 0.8),
+            map: bricks,
+        });
+        world.add_hittable(&make_sphere(Vec3::new(7.5, 1.0, 3.0), 1.0, &ball));
+        world.update_metadata();
+
+        world
+    }
+}
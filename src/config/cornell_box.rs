@@ -1,25 +1,25 @@
 use std::sync::Arc;
 
 use crate::config::SceneConfig;
-use crate::object::constant_medium::ConstantMedium;
 use crate::object::cube::Cube;
 use crate::object::material::DiffuseLight;
+use crate::object::quad::Quad;
 use crate::object::rect::{XYRect, XZRect, YZRect};
 use crate::object::rotate::RotateY;
 use crate::object::{
     make_hittable, make_material, make_texture, LambertianDiffuse, SolidColor, World,
 };
+use crate::render::camera::{make_camera, CameraObject, PerspectiveCamera};
 use crate::render::skybox::{ColorGradientSkyBox, SkyBox};
-use crate::render::Camera;
 use crate::utils::{Color, Vec3};
 
 pub struct CornellBoxScene {}
 
 impl SceneConfig for CornellBoxScene {
-    fn get_camera(&self) -> Camera {
+    fn get_camera(&self) -> CameraObject {
         let look_from = Vec3::new(273.0, 273.0, 1300.0);
         let look_at = Vec3::new(273.0, 273.0, 0.0);
-        Camera::look_from(
+        make_camera(PerspectiveCamera::look_from(
             look_from,
             look_at,
             Vec3::new(0.0, 1.0, 0.0),
@@ -29,7 +29,7 @@ impl SceneConfig for CornellBoxScene {
             (look_at - look_from).length(),
             0.0,
             0.01,
-        )
+        ))
     }
 
     fn get_world(&self) -> World {
@@ -53,7 +53,12 @@ impl SceneConfig for CornellBoxScene {
         let up = make_hittable(XZRect::new((0.0, 0.0), (555.0, 555.0), 555.0, &white));
         let down = make_hittable(XZRect::new((0.0, 0.0), (555.0, 555.0), 0.0, &white));
         let behind = make_hittable(XYRect::new((0.0, 0.0), (555.0, 555.0), 0.0, &white));
-        let lamp = make_hittable(XZRect::new((213.0, 227.0), (343.0, 332.0), 554.0, &light));
+        let lamp = make_hittable(Quad::new(
+            Vec3::new(213.0, 554.0, 227.0),
+            Vec3::new(130.0, 0.0, 0.0),
+            Vec3::new(0.0, 0.0, 105.0),
+            &light,
+        ));
 
         let c1 = make_hittable(Cube::new(
             Vec3::new(265.0, 0.0, 295.0),
@@ -67,14 +72,13 @@ impl SceneConfig for CornellBoxScene {
         ));
         let rc1 = make_hittable(RotateY::new(&c1, -5.0));
         let rc2 = make_hittable(RotateY::new(&c2, 10.0));
-        let black_color = make_texture(SolidColor::new(0.0, 0.0, 0.0));
-        let white_color = make_texture(SolidColor::new(1.0, 1.0, 1.0));
         world.add_hittable(&left);
         world.add_hittable(&right);
         world.add_hittable(&down);
         world.add_hittable(&up);
         world.add_hittable(&behind);
         world.add_hittable(&lamp);
+        world.add_light(&lamp);
         world.add_hittable(&rc1);
         world.add_hittable(&rc2);
 
@@ -0,0 +1,113 @@
+//! configuration for the cornell box filled with procedural smoke & fire
+
+use std::sync::Arc;
+
+use crate::config::SceneConfig;
+use crate::object::cube::Cube;
+use crate::object::heterogeneous_medium::{DensityFieldObject, HeterogeneousMedium, NoiseDensity};
+use crate::object::material::DiffuseLight;
+use crate::object::phase::{HenyeyGreenstein, PhaseFunctionObject};
+use crate::object::rect::{XYRect, XZRect, YZRect};
+use crate::object::rotate::RotateY;
+use crate::object::{
+    make_hittable, make_material, make_sphere, make_texture, LambertianDiffuse, SolidColor, World,
+};
+use crate::render::camera::{make_camera, CameraObject, PerspectiveCamera};
+use crate::render::skybox::{ColorGradientSkyBox, SkyBox};
+use crate::utils::perlin::Perlin;
+use crate::utils::{Color, Vec3};
+
+pub struct CornellSmokeScene {}
+
+impl SceneConfig for CornellSmokeScene {
+    fn get_camera(&self) -> CameraObject {
+        let look_from = Vec3::new(273.0, 273.0, 1300.0);
+        let look_at = Vec3::new(273.0, 273.0, 0.0);
+        make_camera(PerspectiveCamera::look_from(
+            look_from,
+            look_at,
+            Vec3::new(0.0, 1.0, 0.0),
+            40.0,
+            1.0,
+            0.0,
+            (look_at - look_from).length(),
+            0.0,
+            0.01,
+        ))
+    }
+
+    fn get_world(&self) -> World {
+        let red = make_material(LambertianDiffuse {
+            texture: make_texture(SolidColor::new(0.65, 0.05, 0.05)),
+        });
+        let white = make_material(LambertianDiffuse {
+            texture: make_texture(SolidColor::new(0.73, 0.73, 0.73)),
+        });
+        let green = make_material(LambertianDiffuse {
+            texture: make_texture(SolidColor::new(0.12, 0.45, 0.15)),
+        });
+        let light = make_material(DiffuseLight {
+            emit: make_texture(SolidColor::new(1.0, 1.0, 1.0)),
+            brightness: 7.0,
+        });
+        let mut world = World::new();
+        // generate walls
+        let left = make_hittable(YZRect::new((0.0, 0.0), (555.0, 555.0), 555.0, &green));
+        let right = make_hittable(YZRect::new((0.0, 0.0), (555.0, 555.0), 0.0, &red));
+        let up = make_hittable(XZRect::new((0.0, 0.0), (555.0, 555.0), 555.0, &white));
+        let down = make_hittable(XZRect::new((0.0, 0.0), (555.0, 555.0), 0.0, &white));
+        let behind = make_hittable(XYRect::new((0.0, 0.0), (555.0, 555.0), 0.0, &white));
+        let lamp = make_hittable(XZRect::new((113.0, 127.0), (443.0, 432.0), 554.0, &light));
+        world.add_hittable(&left);
+        world.add_hittable(&right);
+        world.add_hittable(&down);
+        world.add_hittable(&up);
+        world.add_hittable(&behind);
+        world.add_hittable(&lamp);
+
+        let perlin = Arc::new(Perlin::new());
+        // a tall box of white smoke
+        let c = make_hittable(Cube::new(
+            Vec3::new(130.0, 1.0, 100.0),
+            Vec3::new(295.0, 330.0, 300.0),
+            &white,
+        ));
+        let boundary = make_hittable(RotateY::new(&c, 10.0));
+        let smoke: DensityFieldObject = Arc::new(NoiseDensity {
+            generator: perlin.clone(),
+            scale: 0.02,
+            depth: 5,
+            density: 0.02,
+        });
+        let smoke_tex = make_texture(SolidColor::new(0.9, 0.9, 0.9));
+        let mut smoke = HeterogeneousMedium::new(&boundary, &smoke, &smoke_tex);
+        // smoke particles mostly scatter forward
+        let phase: PhaseFunctionObject = Arc::new(HenyeyGreenstein::new(0.6));
+        smoke.set_phase_function(&phase);
+        world.add_hittable(&make_hittable(smoke));
+
+        // a ball of fire, mostly absorbing & glowing orange
+        let boundary = make_sphere(Vec3::new(370.0, 120.0, 380.0), 110.0, &white);
+        let flame: DensityFieldObject = Arc::new(NoiseDensity {
+            generator: perlin,
+            scale: 0.03,
+            depth: 4,
+            density: 0.008,
+        });
+        let soot = make_texture(SolidColor::new(0.2, 0.2, 0.2));
+        let mut fire = HeterogeneousMedium::new(&boundary, &flame, &soot);
+        fire.set_coefficients(0.3, 0.7);
+        fire.set_emission(&make_texture(SolidColor::new(1.0, 0.45, 0.1)), 3.0);
+        world.add_hittable(&make_hittable(fire));
+
+        // set up a dark skybox
+        let sb: Arc<dyn SkyBox + Send + Sync> = Arc::new(ColorGradientSkyBox {
+            v1: Color::zero(),
+            v2: Color::zero(),
+        });
+        world.set_skybox(&sb);
+
+        world.update_metadata();
+        world
+    }
+}
@@ -0,0 +1,110 @@
+//! configuration for cut-out geometry: a wire fence & a bush of leaves on textured quads
+
+use std::f64::consts::PI;
+use std::sync::Arc;
+
+use crate::config::SceneConfig;
+use crate::io::file::read_picture;
+use crate::object::alpha::{AlphaMask, AlphaMode};
+use crate::object::plane::Plane;
+use crate::object::quad::Quad;
+use crate::object::texture::{ImageTexture, WrapMode};
+use crate::object::{
+    make_hittable, make_material, make_sphere, make_texture, LambertianDiffuse, SolidColor,
+    TextureObject, World,
+};
+use crate::render::camera::{make_camera, CameraObject, PerspectiveCamera};
+use crate::render::skybox::{ColorGradientSkyBox, SkyBox};
+use crate::utils::{Color, Vec3};
+
+pub struct CutoutScene {}
+
+impl SceneConfig for CutoutScene {
+    fn get_camera(&self) -> CameraObject {
+        let look_from = Vec3::new(1.0, 1.8, 6.0);
+        let look_at = Vec3::new(0.0, 0.8, 0.0);
+        make_camera(PerspectiveCamera::look_from(
+            look_from,
+            look_at,
+            Vec3::new(0.0, 1.0, 0.0),
+            40.0,
+            1.5,
+            0.0,
+            (look_at - look_from).length(),
+            0.0,
+            0.01,
+        ))
+    }
+
+    fn get_world(&self) -> World {
+        let mut world = World::new();
+        let sb: Arc<dyn SkyBox + Send + Sync> = Arc::new(ColorGradientSkyBox {
+            v1: Color::new(1.0, 1.0, 1.0),
+            v2: Color::new(0.5, 0.7, 1.0),
+        });
+        world.set_skybox(&sb);
+        let diffuse = |r, g, b| {
+            make_material(LambertianDiffuse {
+                texture: make_texture(SolidColor::new(r, g, b)),
+            })
+        };
+        world.add_hittable(&make_hittable(Plane::new(
+            Vec3::zero(),
+            Vec3::new(0.0, 1.0, 0.0),
+            &diffuse(0.4, 0.35, 0.3),
+        )));
+        world.add_hittable(&make_sphere(
+            Vec3::new(-0.8, 0.6, -1.5),
+            0.6,
+            &diffuse(0.8, 0.2, 0.1),
+        ));
+
+        // cut-out textures are clamped, so their edges don't blend with the opposite edges
+        let cutout = |path| {
+            let mut texture = ImageTexture::new(Arc::new(read_picture(path)));
+            texture.set_wrap(WrapMode::Clamp);
+            make_texture(texture)
+        };
+
+        // a fence with crisp wires in front of the ball
+        let fence: TextureObject = cutout("assets/textures/fence.png");
+        let panel = make_hittable(Quad::new(
+            Vec3::new(-2.5, 0.0, -0.5),
+            Vec3::new(3.0, 0.0, 0.0),
+            Vec3::new(0.0, 1.5, 0.0),
+            &make_material(LambertianDiffuse {
+                texture: fence.clone(),
+            }),
+        ));
+        world.add_hittable(&make_hittable(AlphaMask::new(
+            &panel,
+            &fence,
+            AlphaMode::Threshold(0.5),
+        )));
+
+        // a bush of crossed leaf cards with soft edges
+        let leaves: TextureObject = cutout("assets/textures/leaves.png");
+        let leaf_mat = make_material(LambertianDiffuse {
+            texture: leaves.clone(),
+        });
+        let (center, size) = (Vec3::new(1.3, 0.0, 0.5), 1.6);
+        for i in 0..3 {
+            let angle = i as f64 * PI / 3.0;
+            let across = Vec3::new(angle.cos(), 0.0, angle.sin()) * size;
+            let card = make_hittable(Quad::new(
+                center - across * 0.5,
+                across,
+                Vec3::new(0.0, size, 0.0),
+                &leaf_mat,
+            ));
+            world.add_hittable(&make_hittable(AlphaMask::new(
+                &card,
+                &leaves,
+                AlphaMode::Stochastic,
+            )));
+        }
+        world.update_metadata();
+
+        world
+    }
+}
@@ -0,0 +1,86 @@
+//! configuration for meshes read from PLY & STL files, shaded with vertex colors & materials
+
+use std::sync::Arc;
+
+use crate::config::SceneConfig;
+use crate::io::ply::read_ply;
+use crate::io::stl::read_stl;
+use crate::object::instance::Instance;
+use crate::object::plane::Plane;
+use crate::object::texture::{CheckerTexture, SolidColor};
+use crate::object::{make_hittable, make_material, LambertianDiffuse, Metal, World};
+use crate::render::camera::{make_camera, CameraObject, PerspectiveCamera};
+use crate::render::skybox::{ColorGradientSkyBox, SkyBox};
+use crate::utils::transform::Transform;
+use crate::utils::{Color, Vec3};
+
+pub struct MeshScene {}
+
+impl SceneConfig for MeshScene {
+    fn get_camera(&self) -> CameraObject {
+        let look_from = Vec3::new(0.0, 2.5, 7.0);
+        let look_at = Vec3::new(0.0, 0.7, 0.0);
+        make_camera(PerspectiveCamera::look_from(
+            look_from,
+            look_at,
+            Vec3::new(0.0, 1.0, 0.0),
+            35.0,
+            1.5,
+            0.0,
+            (look_at - look_from).length(),
+            0.0,
+            0.01,
+        ))
+    }
+
+    fn get_world(&self) -> World {
+        let mut world = World::new();
+        let sb: Arc<dyn SkyBox + Send + Sync> = Arc::new(ColorGradientSkyBox {
+            v1: Color::new(1.0, 1.0, 1.0),
+            v2: Color::new(0.5, 0.7, 1.0),
+        });
+        world.set_skybox(&sb);
+        let ground = make_material(LambertianDiffuse {
+            texture: Arc::new(CheckerTexture {
+                odd_color: Arc::new(SolidColor::new(0.2, 0.3, 0.1)),
+                even_color: Arc::new(SolidColor::new(0.9, 0.9, 0.9)),
+            }),
+        });
+        world.add_hittable(&make_hittable(Plane::new(
+            Vec3::new(0.0, -0.01, 0.0),
+            Vec3::new(0.0, 1.0, 0.0),
+            &ground,
+        )));
+
+        // a scanned ball with colors & smooth normals, left as is & in polished metal
+        let ball = read_ply("assets/meshes/icosphere.ply").expect("Failed to read mesh.");
+        let ball = make_hittable(ball.to_colored_container());
+        world.add_hittable(&make_hittable(Instance::new(
+            &ball,
+            Transform::new(Vec3::new(-1.3, 1.0, 0.0), Vec3::zero(), 1.0),
+        )));
+        let mut shiny = Instance::new(
+            &ball,
+            Transform::new(Vec3::new(2.0, 0.6, -1.0), Vec3::zero(), 0.6),
+        );
+        shiny.set_material(&make_material(Metal {
+            fuzziness: 0.05,
+            albedo: Color::new(0.8, 0.8, 0.9),
+        }));
+        world.add_hittable(&make_hittable(shiny));
+
+        // a CAD part
+        let star = read_stl("assets/meshes/star.stl").expect("Failed to read mesh.");
+        let star = make_hittable(star.to_container(&make_material(Metal {
+            fuzziness: 0.3,
+            albedo: Color::new(0.9, 0.6, 0.3),
+        })));
+        world.add_hittable(&make_hittable(Instance::new(
+            &star,
+            Transform::new(Vec3::new(0.9, 0.0, 1.2), Vec3::new(0.0, 15.0, 0.0), 0.8),
+        )));
+        world.update_metadata();
+
+        world
+    }
+}
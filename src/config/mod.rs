@@ -1,13 +1,22 @@
 use crate::object::World;
-use crate::render::Camera;
+use crate::render::camera::CameraObject;
 
+pub mod animation;
+pub mod bokeh;
+pub mod bump;
 pub mod cornell_box;
+pub mod cornell_smoke;
+pub mod cutout;
+pub mod mesh;
 pub mod next_week_final_scene;
 pub mod random_spheres;
 pub mod random_spheres_night;
+pub mod shapes;
+pub mod terrain;
+pub mod texture_filter;
 pub mod two_spheres;
 
 pub trait SceneConfig {
-    fn get_camera(&self) -> Camera;
+    fn get_camera(&self) -> CameraObject;
     fn get_world(&self) -> World;
 }
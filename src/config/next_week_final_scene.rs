@@ -7,6 +7,7 @@ use crate::io::file::read_picture;
 use crate::object::constant_medium::ConstantMedium;
 use crate::object::container::Container;
 use crate::object::cube::Cube;
+use crate::object::instance::Instance;
 use crate::object::material::DiffuseLight;
 use crate::object::rect::XZRect;
 use crate::object::texture::ImageTexture;
@@ -14,18 +15,19 @@ use crate::object::{
     make_hittable, make_material, make_sphere, make_texture, Dielectric, LambertianDiffuse,
     MaterialObject, Metal, MovingSphere, NoiseTexture, SolidColor, Sphere, World,
 };
+use crate::render::camera::{make_camera, CameraObject, PerspectiveCamera};
 use crate::render::skybox::{ColorGradientSkyBox, SkyBox};
-use crate::render::Camera;
 use crate::utils::perlin::Perlin;
+use crate::utils::transform::Transform;
 use crate::utils::{Color, Vec3};
 
 pub struct NextWeekFinalScene {}
 
 impl SceneConfig for NextWeekFinalScene {
-    fn get_camera(&self) -> Camera {
+    fn get_camera(&self) -> CameraObject {
         let look_from = Vec3::new(478.0, 278.0, -600.0);
         let look_at = Vec3::new(278.0, 278.0, 0.0);
-        Camera::look_from(
+        make_camera(PerspectiveCamera::look_from(
             look_from,
             look_at,
             Vec3::new(0.0, 1.0, 0.0),
@@ -35,7 +37,7 @@ impl SceneConfig for NextWeekFinalScene {
             (look_at - look_from).length(),
             0.0,
             1.0,
-        )
+        ))
     }
 
     fn get_world(&self) -> World {
@@ -46,22 +48,23 @@ impl SceneConfig for NextWeekFinalScene {
         let ground = make_material(LambertianDiffuse {
             texture: Arc::new(SolidColor::new(0.48, 0.83, 0.53)),
         });
+        // every box is an instance of one cube, sunk into the ground down to its height
+        let w = 100.0;
+        let block = make_hittable(Cube::new(
+            Vec3::new(0.0, -w, 0.0),
+            Vec3::new(w, 0.0, w),
+            &ground,
+        ));
         let mut rng = thread_rng();
         for i in 0..BOXES_PER_SIDE {
             for j in 0..BOXES_PER_SIDE {
-                let w = 100.0;
                 let x0 = -1000.0 + i as f64 * w;
                 let z0 = -1000.0 + j as f64 * w;
-                let y0 = 0.0;
-                let x1 = x0 + w;
                 let y1 = rng.gen_range(1.0, 101.0);
-                let z1 = z0 + w;
-                let c = make_hittable(Cube::new(
-                    Vec3::new(x0, y0, z0),
-                    Vec3::new(x1, y1, z1),
-                    &ground,
-                ));
-                c1.add_hittable(&c);
+                c1.add_hittable(&make_hittable(Instance::new(
+                    &block,
+                    Transform::new(Vec3::new(x0, y1, z0), Vec3::zero(), 1.0),
+                )));
             }
         }
         c1.update_metadata();
@@ -131,26 +134,29 @@ impl SceneConfig for NextWeekFinalScene {
 
         // earth sphere
         let emat = make_material(LambertianDiffuse {
-            texture: Arc::new(ImageTexture {
-                image: Arc::new(read_picture("assets/textures/earthmap.jpg")),
-            }),
+            texture: Arc::new(ImageTexture::new(Arc::new(read_picture(
+                "assets/textures/earthmap.jpg",
+            )))),
         });
         world.add_hittable(&make_sphere(Vec3::new(400.0, 200.0, 400.0), 100.0, &emat));
 
-        // a cube of little balls
+        // a cube of little balls with its own BVH, placed & turned as a single instance
         let mut cc = Container::new();
         let white_mat = make_material(LambertianDiffuse {
             texture: white.clone(),
         });
         for _j in 0..1000 {
-            cc.add_hittable(&make_sphere(
-                Vec3::random(0.0, 165.0) + Vec3::new(-100.0, 270.0, 395.0),
-                10.0,
-                &white_mat,
-            ));
+            cc.add_hittable(&make_sphere(Vec3::random(0.0, 165.0), 10.0, &white_mat));
         }
         cc.update_metadata();
-        world.add_hittable(&make_hittable(cc));
+        world.add_hittable(&make_hittable(Instance::new(
+            &make_hittable(cc),
+            Transform::new(
+                Vec3::new(-100.0, 270.0, 395.0),
+                Vec3::new(0.0, 15.0, 0.0),
+                1.0,
+            ),
+        )));
 
         // perlin noise ball
         let perlin = Arc::new(Perlin::new());
@@ -10,7 +10,7 @@ use crate::object::texture::{CheckerTexture, SolidColor};
 use crate::object::{
     make_bouncing_sphere, make_material, make_sphere, LambertianDiffuse, Metal, World,
 };
-use crate::render::Camera;
+use crate::render::camera::{make_camera, CameraObject, PerspectiveCamera};
 use crate::utils::Vec3;
 
 pub struct RandomSpheresScene {
@@ -19,10 +19,10 @@ pub struct RandomSpheresScene {
 
 impl SceneConfig for RandomSpheresScene {
     // configure the camera
-    fn get_camera(&self) -> Camera {
+    fn get_camera(&self) -> CameraObject {
         let look_from = Vec3::new(13.0, 2.0, 4.0);
         let look_at = Vec3::new(0.0, 0.0, 0.0);
-        Camera::look_from(
+        make_camera(PerspectiveCamera::look_from(
             look_from,
             look_at,
             Vec3::new(0.0, 1.0, 0.0),
@@ -32,7 +32,7 @@ impl SceneConfig for RandomSpheresScene {
             (look_at - look_from).length(),
             0.0,
             0.25,
-        )
+        ))
     }
 
     // configure the random sphere scene
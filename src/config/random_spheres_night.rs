@@ -11,8 +11,8 @@ use crate::object::texture::{CheckerTexture, SolidColor};
 use crate::object::{
     make_bouncing_sphere, make_material, make_sphere, LambertianDiffuse, Metal, World,
 };
+use crate::render::camera::{make_camera, CameraObject, PerspectiveCamera};
 use crate::render::skybox::{ColorGradientSkyBox, SkyBox};
-use crate::render::Camera;
 use crate::utils::{Color, Vec3};
 
 pub struct RandomSpheresNightScene {
@@ -21,10 +21,10 @@ pub struct RandomSpheresNightScene {
 
 impl SceneConfig for RandomSpheresNightScene {
     // configure the camera
-    fn get_camera(&self) -> Camera {
+    fn get_camera(&self) -> CameraObject {
         let look_from = Vec3::new(12.0, 3.0, 4.0);
         let look_at = Vec3::new(0.0, 0.0, 0.0);
-        Camera::look_from(
+        make_camera(PerspectiveCamera::look_from(
             look_from,
             look_at,
             Vec3::new(0.0, 1.0, 0.0),
@@ -34,7 +34,7 @@ impl SceneConfig for RandomSpheresNightScene {
             (look_at - look_from).length(),
             0.0,
             0.25,
-        )
+        ))
     }
 
     // configure the random sphere scene
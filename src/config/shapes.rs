@@ -0,0 +1,188 @@
+//! configuration for a showcase of the analytic primitives, flat panels, CSG & distance fields on an infinite ground plane
+
+use std::sync::Arc;
+
+use crate::config::SceneConfig;
+use crate::object::aabb::AABB;
+use crate::object::csg::Csg;
+use crate::object::cube::Cube;
+use crate::object::cylinder::{Capsule, Cone, Cylinder};
+use crate::object::plane::{Disk, Plane};
+use crate::object::quad::Quad;
+use crate::object::sdf::{
+    make_distance_function, SdfBox, SdfObject, SdfSphere, SdfTorus, SmoothUnion, Twist,
+};
+use crate::object::texture::SolidColor;
+use crate::object::torus::Torus;
+use crate::object::triangle::Triangle;
+use crate::object::{
+    make_hittable, make_material, make_sphere, Dielectric, LambertianDiffuse, Metal, World,
+};
+use crate::render::camera::{make_camera, CameraObject, PerspectiveCamera};
+use crate::render::skybox::{ColorGradientSkyBox, SkyBox};
+use crate::utils::{Color, Vec3};
+
+pub struct ShapesScene {}
+
+impl SceneConfig for ShapesScene {
+    fn get_camera(&self) -> CameraObject {
+        let look_from = Vec3::new(0.0, 3.0, 9.0);
+        let look_at = Vec3::new(0.0, 0.8, 0.0);
+        make_camera(PerspectiveCamera::look_from(
+            look_from,
+            look_at,
+            Vec3::new(0.0, 1.0, 0.0),
+            35.0,
+            1.5,
+            0.0,
+            (look_at - look_from).length(),
+            0.0,
+            0.01,
+        ))
+    }
+
+    fn get_world(&self) -> World {
+        let mut world = World::new();
+        let sb: Arc<dyn SkyBox + Send + Sync> = Arc::new(ColorGradientSkyBox {
+            v1: Color::new(1.0, 1.0, 1.0),
+            v2: Color::new(0.5, 0.7, 1.0),
+        });
+        world.set_skybox(&sb);
+        let diffuse = |r, g, b| {
+            make_material(LambertianDiffuse {
+                texture: Arc::new(SolidColor::new(r, g, b)),
+            })
+        };
+        let ground = diffuse(0.5, 0.5, 0.5);
+        world.add_hittable(&make_hittable(Plane::new(
+            Vec3::zero(),
+            Vec3::new(0.0, 1.0, 0.0),
+            &ground,
+        )));
+
+        // a column & a pipe lying on the ground
+        world.add_hittable(&make_hittable(Cylinder::new(
+            Vec3::new(-3.0, 0.0, -1.0),
+            Vec3::new(-3.0, 2.5, -1.0),
+            0.5,
+            true,
+            &diffuse(0.8, 0.8, 0.7),
+        )));
+        world.add_hittable(&make_hittable(Cylinder::new(
+            Vec3::new(-2.0, 0.3, 1.5),
+            Vec3::new(0.0, 0.3, 2.5),
+            0.3,
+            false,
+            &diffuse(0.2, 0.4, 0.8),
+        )));
+        world.add_hittable(&make_hittable(Cone::new(
+            Vec3::new(-1.0, 0.0, -0.5),
+            Vec3::new(-1.0, 1.8, -0.5),
+            0.6,
+            true,
+            &diffuse(0.8, 0.3, 0.1),
+        )));
+        // a standing metal ring
+        let metal = make_material(Metal {
+            fuzziness: 0.1,
+            albedo: Vec3::new(0.8, 0.7, 0.3),
+        });
+        world.add_hittable(&make_hittable(Torus::new(
+            Vec3::new(1.0, 1.1, -0.5),
+            Vec3::new(0.3, 0.0, 1.0),
+            0.8,
+            0.25,
+            &metal,
+        )));
+        world.add_hittable(&make_hittable(Capsule::new(
+            Vec3::new(2.5, 0.4, 1.2),
+            Vec3::new(3.2, 1.6, 0.6),
+            0.4,
+            &make_material(Dielectric::new(1.5, Vec3::one())),
+        )));
+        world.add_hittable(&make_hittable(Disk::new(
+            Vec3::new(3.5, 1.0, -2.0),
+            Vec3::new(-0.5, 0.2, 1.0),
+            1.0,
+            &diffuse(0.2, 0.7, 0.3),
+        )));
+
+        // a glass lens carved from two spheres & a cube with a spherical hole
+        let glass = make_material(Dielectric::new(1.5, Vec3::one()));
+        let lens = Csg::intersection(
+            &make_sphere(Vec3::new(0.0, 0.6, 2.2), 1.0, &glass),
+            &make_sphere(Vec3::new(0.0, 0.6, 3.8), 1.0, &glass),
+        );
+        world.add_hittable(&make_hittable(lens));
+        let white = diffuse(0.9, 0.9, 0.9);
+        let holed_cube = Csg::difference(
+            &make_hittable(Cube::new(
+                Vec3::new(1.2, 0.0, 1.8),
+                Vec3::new(2.2, 1.0, 2.8),
+                &white,
+            )),
+            &make_sphere(Vec3::new(1.7, 0.5, 2.8), 0.6, &white),
+        );
+        world.add_hittable(&make_hittable(holed_cube));
+
+        // a tilted panel & a triangle, without rotation wrappers
+        world.add_hittable(&make_hittable(Quad::new(
+            Vec3::new(4.0, 0.0, -1.0),
+            Vec3::new(0.8, 0.0, 1.2),
+            Vec3::new(-0.4, 1.8, 0.0),
+            &diffuse(0.8, 0.8, 0.2),
+        )));
+        world.add_hittable(&make_hittable(Triangle::new(
+            [
+                Vec3::new(-1.5, 0.0, -3.5),
+                Vec3::new(0.5, 0.0, -3.5),
+                Vec3::new(-0.5, 2.5, -3.0),
+            ],
+            &diffuse(0.3, 0.7, 0.8),
+        )));
+
+        // distance fields: a twisted column & a ball smoothly blended into a ring
+        let twisted = make_distance_function(Twist {
+            inner: make_distance_function(SdfBox {
+                center: Vec3::zero(),
+                half_size: Vec3::new(0.35, 1.2, 0.35),
+            }),
+            rate: 60.0,
+        });
+        let offset = Vec3::new(-4.5, 1.2, -3.0);
+        let column = make_distance_function(move |p| twisted.distance(p - offset));
+        let mut column = SdfObject::new(
+            &column,
+            AABB {
+                min: offset - Vec3::new(0.5, 1.2, 0.5),
+                max: offset + Vec3::new(0.5, 1.2, 0.5),
+            },
+            &diffuse(0.6, 0.3, 0.7),
+        );
+        column.set_step_scale(0.5);
+        world.add_hittable(&make_hittable(column));
+        let blob = make_distance_function(SmoothUnion {
+            a: make_distance_function(SdfSphere {
+                center: Vec3::new(-2.9, 0.7, 3.2),
+                radius: 0.5,
+            }),
+            b: make_distance_function(SdfTorus {
+                center: Vec3::new(-2.9, 0.2, 3.2),
+                major_radius: 0.9,
+                minor_radius: 0.2,
+            }),
+            k: 0.3,
+        });
+        world.add_hittable(&make_hittable(SdfObject::new(
+            &blob,
+            AABB {
+                min: Vec3::new(-4.1, 0.0, 2.0),
+                max: Vec3::new(-1.7, 1.3, 4.4),
+            },
+            &diffuse(0.9, 0.6, 0.2),
+        )));
+        world.update_metadata();
+
+        world
+    }
+}
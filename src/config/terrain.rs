@@ -0,0 +1,128 @@
+//! configuration for a noise terrain by a lake, built as a heightfield & covered by an instanced
+//! forest
+
+use std::sync::Arc;
+
+use rand::prelude::StdRng;
+use rand::{Rng, SeedableRng};
+
+use crate::config::SceneConfig;
+use crate::object::container::Container;
+use crate::object::cylinder::{Cone, Cylinder};
+use crate::object::heightfield::Heightfield;
+use crate::object::instance::Instance;
+use crate::object::plane::Plane;
+use crate::object::texture::SolidColor;
+use crate::object::{make_hittable, make_material, LambertianDiffuse, Metal, World};
+use crate::render::camera::{make_camera, CameraObject, PerspectiveCamera};
+use crate::render::skybox::{ColorGradientSkyBox, SkyBox};
+use crate::utils::perlin::Perlin;
+use crate::utils::transform::Transform;
+use crate::utils::{Color, Ray, Vec3};
+
+/// Number of trees tried, only those above the lake are planted.
+const TREES: usize = 3000;
+
+pub struct TerrainScene {}
+
+impl SceneConfig for TerrainScene {
+    fn get_camera(&self) -> CameraObject {
+        let look_from = Vec3::new(0.0, 7.0, 16.0);
+        let look_at = Vec3::new(0.0, 1.0, 0.0);
+        make_camera(PerspectiveCamera::look_from(
+            look_from,
+            look_at,
+            Vec3::new(0.0, 1.0, 0.0),
+            40.0,
+            1.5,
+            0.0,
+            (look_at - look_from).length(),
+            0.0,
+            0.01,
+        ))
+    }
+
+    fn get_world(&self) -> World {
+        let mut world = World::new();
+        let sb: Arc<dyn SkyBox + Send + Sync> = Arc::new(ColorGradientSkyBox {
+            v1: Color::new(1.0, 1.0, 1.0),
+            v2: Color::new(0.5, 0.7, 1.0),
+        });
+        world.set_skybox(&sb);
+
+        let grass = make_material(LambertianDiffuse {
+            texture: Arc::new(SolidColor::new(0.35, 0.55, 0.25)),
+        });
+        let terrain = make_hittable(Heightfield::from_perlin(
+            &Perlin::new(),
+            0.15,
+            (200, 200),
+            Vec3::new(-15.0, -1.0, -15.0),
+            (30.0, 30.0),
+            6.0,
+            &grass,
+        ));
+        world.add_hittable(&terrain);
+        // the lake fills the valleys
+        let water = make_material(Metal {
+            fuzziness: 0.02,
+            albedo: Vec3::new(0.5, 0.6, 0.8),
+        });
+        world.add_hittable(&make_hittable(Plane::new(
+            Vec3::new(0.0, 1.0, 0.0),
+            Vec3::new(0.0, 1.0, 0.0),
+            &water,
+        )));
+
+        // a forest of instances of one tree, standing on the terrain above the lake
+        let mut tree = Container::new();
+        let bark = make_material(LambertianDiffuse {
+            texture: Arc::new(SolidColor::new(0.3, 0.2, 0.1)),
+        });
+        let leaves = make_material(LambertianDiffuse {
+            texture: Arc::new(SolidColor::new(0.1, 0.3, 0.1)),
+        });
+        tree.add_hittable(&make_hittable(Cylinder::new(
+            Vec3::zero(),
+            Vec3::new(0.0, 0.3, 0.0),
+            0.05,
+            true,
+            &bark,
+        )));
+        tree.add_hittable(&make_hittable(Cone::new(
+            Vec3::new(0.0, 0.2, 0.0),
+            Vec3::new(0.0, 1.0, 0.0),
+            0.25,
+            true,
+            &leaves,
+        )));
+        tree.update_metadata();
+        let tree = make_hittable(tree);
+        let mut forest = Container::new();
+        let mut rng = StdRng::seed_from_u64(7);
+        for _ in 0..TREES {
+            let (x, z) = (rng.gen_range(-15.0, 15.0), rng.gen_range(-15.0, 15.0));
+            let down = Ray {
+                orig: Vec3::new(x, 10.0, z),
+                dir: Vec3::new(0.0, -1.0, 0.0),
+                t: 0.0,
+            };
+            match terrain.hit(&down, 0.0, f64::INFINITY) {
+                Some(h) if h.p.y > 1.2 => {
+                    let transform = Transform::new(
+                        h.p,
+                        Vec3::new(0.0, rng.gen_range(0.0, 360.0), 0.0),
+                        rng.gen_range(0.6, 1.4),
+                    );
+                    forest.add_hittable(&make_hittable(Instance::new(&tree, transform)));
+                }
+                _ => {}
+            }
+        }
+        forest.update_metadata();
+        world.add_hittable(&make_hittable(forest));
+        world.update_metadata();
+
+        world
+    }
+}
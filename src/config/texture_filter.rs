@@ -0,0 +1,79 @@
+//! configuration for filtered image textures: a checkered ground receding to the horizon & panels
+//! in each wrap mode
+
+use std::sync::Arc;
+
+use crate::config::SceneConfig;
+use crate::io::file::read_picture;
+use crate::object::plane::Plane;
+use crate::object::quad::Quad;
+use crate::object::texture::{ImageTexture, TextureFilter, WrapMode};
+use crate::object::{make_hittable, make_material, make_texture, LambertianDiffuse, World};
+use crate::render::camera::{make_camera, CameraObject, PerspectiveCamera};
+use crate::render::skybox::{ColorGradientSkyBox, SkyBox};
+use crate::utils::{Color, Vec3};
+
+pub struct TextureFilterScene {}
+
+impl SceneConfig for TextureFilterScene {
+    fn get_camera(&self) -> CameraObject {
+        let look_from = Vec3::new(0.0, 1.6, 7.0);
+        let look_at = Vec3::new(0.0, 1.0, 0.0);
+        make_camera(PerspectiveCamera::look_from(
+            look_from,
+            look_at,
+            Vec3::new(0.0, 1.0, 0.0),
+            45.0,
+            1.5,
+            0.0,
+            (look_at - look_from).length(),
+            0.0,
+            0.01,
+        ))
+    }
+
+    fn get_world(&self) -> World {
+        let mut world = World::new();
+        let sb: Arc<dyn SkyBox + Send + Sync> = Arc::new(ColorGradientSkyBox {
+            v1: Color::new(1.0, 1.0, 1.0),
+            v2: Color::new(0.5, 0.7, 1.0),
+        });
+        world.set_skybox(&sb);
+        let textured = |texture: ImageTexture| {
+            make_material(LambertianDiffuse {
+                texture: make_texture(texture),
+            })
+        };
+
+        // fine checks tiling the ground, which shimmer in the distance without mipmaps
+        let checker = Arc::new(read_picture("assets/textures/checker.png"));
+        world.add_hittable(&make_hittable(Plane::new(
+            Vec3::zero(),
+            Vec3::new(0.0, 1.0, 0.0),
+            &textured(ImageTexture::new(checker)),
+        )));
+
+        // the earth spilling over the edges of the panels, repeated, clamped & mirrored
+        let earth = Arc::new(read_picture("assets/textures/earthmap.jpg"));
+        let panels = [
+            (WrapMode::Repeat, TextureFilter::Bilinear),
+            (WrapMode::Clamp, TextureFilter::Bicubic),
+            (WrapMode::Mirror, TextureFilter::Nearest),
+        ];
+        for (i, &(wrap, filter)) in panels.iter().enumerate() {
+            let mut texture = ImageTexture::new(earth.clone());
+            texture.set_wrap(wrap);
+            texture.set_filter(filter);
+            texture.set_uv_transform((1.5, 1.5), 0.0, (-0.25, -0.25));
+            world.add_hittable(&make_hittable(Quad::new(
+                Vec3::new(-2.9 + 2.0 * i as f64, 0.3, 0.0),
+                Vec3::new(1.8, 0.0, 0.0),
+                Vec3::new(0.0, 1.8, 0.0),
+                &textured(texture),
+            )));
+        }
+        world.update_metadata();
+
+        world
+    }
+}
@@ -7,18 +7,18 @@ use crate::object::texture::{ImageTexture, MarbleTexture};
 use crate::object::{
     make_material, make_sphere, make_texture, LambertianDiffuse, NoiseTexture, World,
 };
+use crate::render::camera::{make_camera, CameraObject, PerspectiveCamera};
 use crate::render::skybox::{ColorGradientSkyBox, SkyBox};
-use crate::render::Camera;
 use crate::utils::perlin::Perlin;
 use crate::utils::{Color, Vec3};
 
 pub struct TwoSpheresScene {}
 
 impl SceneConfig for TwoSpheresScene {
-    fn get_camera(&self) -> Camera {
+    fn get_camera(&self) -> CameraObject {
         let look_from = Vec3::new(13.0, 2.0, 4.0);
         let look_at = Vec3::new(0.0, 0.0, 0.0);
-        Camera::look_from(
+        make_camera(PerspectiveCamera::look_from(
             look_from,
             look_at,
             Vec3::new(0.0, 1.0, 0.0),
@@ -28,7 +28,7 @@ impl SceneConfig for TwoSpheresScene {
             (look_at - look_from).length(),
             0.0,
             0.01,
-        )
+        ))
     }
 
     fn get_world(&self) -> World {
@@ -38,9 +38,9 @@ impl SceneConfig for TwoSpheresScene {
             scale: 2.0,
             turbulence: 10.0,
         });
-        let image_texture = make_texture(ImageTexture {
-            image: Arc::new(read_picture("assets/textures/earthmap.jpg")),
-        });
+        let image_texture = make_texture(ImageTexture::new(Arc::new(read_picture(
+            "assets/textures/earthmap.jpg",
+        ))));
         let glowing_material = make_material(DiffuseLight {
             emit: image_texture.clone(),
             brightness: 10.0,
@@ -5,7 +5,7 @@ use std::io;
 use std::io::BufWriter;
 use std::io::Write;
 
-use image::{open, ImageBuffer, RgbImage};
+use image::{open, ImageBuffer, RgbaImage};
 
 use crate::io::Color24;
 use crate::utils::{Color, Picture};
@@ -18,7 +18,7 @@ pub fn write_to_ppm(p: &Picture, filename: &str) -> io::Result<()> {
     writeln!(&mut stream, "P3")?;
     writeln!(&mut stream, "{} {}", p.width, p.height)?;
     writeln!(&mut stream, "255")?;
-    assert_eq!(p.data.len(), (p.height * p.width) as usize);
+    assert_eq!(p.data.len(), p.height * p.width);
     let mut v: usize = 0;
     for _i in 0..p.width {
         for _j in 0..p.height {
@@ -27,7 +27,7 @@ pub fn write_to_ppm(p: &Picture, filename: &str) -> io::Result<()> {
             write!(&mut stream, " {} {} {}", c.x, c.y, c.z)?;
             v += 1;
         }
-        write!(&mut stream, "\n")?;
+        writeln!(&mut stream)?;
     }
     stream.flush()
 }
@@ -45,8 +45,12 @@ pub fn write_to_png(p: &Picture, filename: &str) {
 }
 
 /// read image file of arbitrary type
+///
+/// The alpha channel is kept for images that have one, like PNG with transparency.
 pub fn read_picture(filename: &str) -> Picture {
-    let buf: RgbImage = open(filename).expect("Failed to read image.").into_rgb();
+    let image = open(filename).expect("Failed to read image.");
+    let has_alpha = image.color().has_alpha();
+    let buf: RgbaImage = image.into_rgba();
     Picture {
         width: buf.width() as usize,
         height: buf.height() as usize,
@@ -60,5 +64,10 @@ pub fn read_picture(filename: &str) -> Picture {
                 )
             })
             .collect(),
+        alpha: if has_alpha {
+            Some(buf.pixels().map(|x| x.0[3] as f32 / 255.0).collect())
+        } else {
+            None
+        },
     }
 }
@@ -0,0 +1,51 @@
+//! lens: read lens descriptions for the lens system camera.
+//!
+//! Every non-empty line not starting with `#` describes a surface, from the scene side to the
+//! film side, by 4 whitespace-separated numbers: curvature radius, thickness, index of
+//! refraction & aperture diameter, e.g. in millimeters. The aperture stop has zero curvature
+//! radius & index of refraction.
+
+use std::fs::read_to_string;
+use std::io;
+
+use crate::render::lens::LensElement;
+
+fn invalid_data(msg: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg)
+}
+
+/// read_lens_description: Read the elements of a lens system from a text file
+pub fn read_lens_description(filename: &str) -> io::Result<Vec<LensElement>> {
+    let content = read_to_string(filename)?;
+    let mut elements = vec![];
+    for (i, line) in content.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let v = line
+            .split_whitespace()
+            .map(|t| t.parse::<f64>())
+            .collect::<Result<Vec<f64>, _>>()
+            .map_err(|_| invalid_data(format!("Invalid number at line {}.", i + 1)))?;
+        if v.len() != 4 {
+            return Err(invalid_data(format!(
+                "Expected 4 numbers at line {}, got {}.",
+                i + 1,
+                v.len()
+            )));
+        }
+        elements.push(LensElement {
+            curvature_radius: v[0],
+            thickness: v[1],
+            ior: v[2],
+            aperture_radius: v[3] / 2.0,
+        });
+    }
+    if elements.is_empty() {
+        return Err(invalid_data(
+            "Lens description has no surfaces.".to_string(),
+        ));
+    }
+    Ok(elements)
+}
@@ -0,0 +1,135 @@
+//! mesh: triangle meshes read from model files, see `ply` & `stl`.
+
+use std::collections::HashMap;
+use std::io;
+use std::sync::Arc;
+
+use crate::object::container::Container;
+use crate::object::texture::SolidColor;
+use crate::object::triangle::Triangle;
+use crate::object::{make_hittable, make_material, HittableObject, LambertianDiffuse};
+use crate::object::{MaterialObject, Texture};
+use crate::utils::{Color, Vec3};
+
+/// Triangles sharing a list of vertices, with optional per-vertex attributes.
+///
+/// Every attribute list present has one entry per vertex.
+#[derive(Default)]
+pub struct TriangleMesh {
+    pub positions: Vec<Vec3<f64>>,
+    pub normals: Option<Vec<Vec3<f64>>>,
+    pub colors: Option<Vec<Color>>,
+    pub uvs: Option<Vec<(f64, f64)>>,
+    pub faces: Vec<[usize; 3]>,
+}
+
+impl TriangleMesh {
+    /// Check that the attributes & faces match the vertices.
+    pub fn validate(&self) -> io::Result<()> {
+        let n = self.positions.len();
+        let lengths = [
+            self.normals.as_ref().map(|v| v.len()),
+            self.colors.as_ref().map(|v| v.len()),
+            self.uvs.as_ref().map(|v| v.len()),
+        ];
+        if lengths.iter().flatten().any(|len| *len != n) {
+            return Err(invalid_data("Vertex attribute count mismatch.".to_string()));
+        }
+        if let Some(f) = self.faces.iter().find(|f| f.iter().any(|i| *i >= n)) {
+            return Err(invalid_data(format!(
+                "Face {:?} refers to missing vertices, there are only {}.",
+                f, n
+            )));
+        }
+        Ok(())
+    }
+
+    /// Set the normal of every vertex to the average normal of the faces around it, weighted by
+    /// their area, for smooth shading of meshes without normals.
+    ///
+    /// Vertices are only shared by faces that index the same vertex, so meshes with a copy of
+    /// every vertex per face, like STL, stay faceted. Vertices whose faces cancel out or are
+    /// degenerate are left with a zero normal, which triangles replace by their own.
+    pub fn compute_normals(&mut self) {
+        let mut normals = vec![Vec3::zero(); self.positions.len()];
+        for f in &self.faces {
+            let [a, b, c] = [
+                self.positions[f[0]],
+                self.positions[f[1]],
+                self.positions[f[2]],
+            ];
+            // the cross product is twice the area along the normal
+            let n = (b - a).cross(c - a);
+            for i in f {
+                normals[*i] += n;
+            }
+        }
+        self.normals = Some(normals);
+    }
+
+    /// Build the triangles of the mesh, all with material `mat`.
+    pub fn triangles(&self, mat: &MaterialObject) -> Vec<HittableObject> {
+        self.faces
+            .iter()
+            .map(|f| make_hittable(self.triangle(f, mat)))
+            .collect()
+    }
+
+    /// Build the triangles of the mesh in a `Container`, all with material `mat`.
+    pub fn to_container(&self, mat: &MaterialObject) -> Container {
+        let mut c = Container::new();
+        c.add_hittables(&self.triangles(mat));
+        c.update_metadata();
+        c
+    }
+
+    /// Build the triangles of the mesh in a `Container`, each one a diffuse material with the
+    /// average color of its vertices, or white if the mesh has no colors.
+    ///
+    /// Materials are shared between triangles of the same 8-bit color.
+    pub fn to_colored_container(&self) -> Container {
+        let mut materials: HashMap<[u8; 3], MaterialObject> = HashMap::new();
+        let mut c = Container::new();
+        for f in &self.faces {
+            let color = match &self.colors {
+                Some(colors) => (colors[f[0]] + colors[f[1]] + colors[f[2]]) / 3.0,
+                None => Color::one(),
+            };
+            let key = [
+                (color.x.clamp(0.0, 1.0) * 255.0).round() as u8,
+                (color.y.clamp(0.0, 1.0) * 255.0).round() as u8,
+                (color.z.clamp(0.0, 1.0) * 255.0).round() as u8,
+            ];
+            let mat = materials.entry(key).or_insert_with(|| {
+                let texture: Arc<dyn Texture + Send + Sync> = Arc::new(SolidColor::new(
+                    key[0] as f32 / 255.0,
+                    key[1] as f32 / 255.0,
+                    key[2] as f32 / 255.0,
+                ));
+                make_material(LambertianDiffuse { texture })
+            });
+            c.add_hittable(&make_hittable(self.triangle(f, mat)));
+        }
+        c.update_metadata();
+        c
+    }
+
+    fn triangle(&self, f: &[usize; 3], mat: &MaterialObject) -> Triangle {
+        let [a, b, c] = *f;
+        let mut t = Triangle::new(
+            [self.positions[a], self.positions[b], self.positions[c]],
+            mat,
+        );
+        if let Some(n) = &self.normals {
+            t.set_normals([n[a], n[b], n[c]]);
+        }
+        if let Some(uv) = &self.uvs {
+            t.set_uvs([uv[a], uv[b], uv[c]]);
+        }
+        t
+    }
+}
+
+pub(crate) fn invalid_data(msg: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg)
+}
@@ -1,6 +1,11 @@
 use crate::utils::{Color, Vec3};
 
 pub mod file;
+pub mod lens;
+pub mod mesh;
+pub mod ply;
+pub mod stl;
+pub mod volume;
 
 /// Color in RGB
 pub type Color24 = Vec3<u8>;
@@ -0,0 +1,310 @@
+//! ply: read triangle meshes from PLY files, in ASCII or binary format.
+//!
+//! Vertices are read with their positions & optional normals (`nx`, `ny`, `nz`), colors (`red`,
+//! `green`, `blue`, as 8-bit integers or floats in [0, 1]) & texture coordinates (`u`, `v` or
+//! `s`, `t`). Meshes without normals get them averaged from the faces. Faces with more than 3
+//! vertices are split into triangle fans. Other elements & properties are skipped.
+
+use std::convert::TryInto;
+use std::fs::read;
+use std::io;
+use std::str::SplitAsciiWhitespace;
+
+use crate::io::mesh::{invalid_data, TriangleMesh};
+use crate::utils::{Color, Vec3};
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+enum Format {
+    Ascii,
+    BinaryLittleEndian,
+    BinaryBigEndian,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+enum Scalar {
+    I8,
+    U8,
+    I16,
+    U16,
+    I32,
+    U32,
+    F32,
+    F64,
+}
+
+impl Scalar {
+    fn from_name(name: &str) -> io::Result<Scalar> {
+        Ok(match name {
+            "char" | "int8" => Scalar::I8,
+            "uchar" | "uint8" => Scalar::U8,
+            "short" | "int16" => Scalar::I16,
+            "ushort" | "uint16" => Scalar::U16,
+            "int" | "int32" => Scalar::I32,
+            "uint" | "uint32" => Scalar::U32,
+            "float" | "float32" => Scalar::F32,
+            "double" | "float64" => Scalar::F64,
+            _ => return Err(invalid_data(format!("Unknown PLY type {}.", name))),
+        })
+    }
+
+    fn size(self) -> usize {
+        match self {
+            Scalar::I8 | Scalar::U8 => 1,
+            Scalar::I16 | Scalar::U16 => 2,
+            Scalar::I32 | Scalar::U32 | Scalar::F32 => 4,
+            Scalar::F64 => 8,
+        }
+    }
+}
+
+enum PropertyType {
+    Scalar(Scalar),
+    /// A list with its length & items.
+    List(Scalar, Scalar),
+}
+
+struct Property {
+    name: String,
+    ty: PropertyType,
+}
+
+struct Element {
+    name: String,
+    count: usize,
+    properties: Vec<Property>,
+}
+
+/// Reader of the values after the header, in either format.
+enum Body<'a> {
+    Ascii(SplitAsciiWhitespace<'a>),
+    Binary { data: &'a [u8], little_endian: bool },
+}
+
+impl<'a> Body<'a> {
+    fn read(&mut self, ty: Scalar) -> io::Result<f64> {
+        let eof = || invalid_data("Unexpected end of PLY data.".to_string());
+        match self {
+            Body::Ascii(tokens) => {
+                let t = tokens.next().ok_or_else(eof)?;
+                t.parse()
+                    .map_err(|_| invalid_data(format!("Invalid PLY value {}.", t)))
+            }
+            Body::Binary {
+                data,
+                little_endian,
+            } => {
+                if data.len() < ty.size() {
+                    return Err(eof());
+                }
+                let (bytes, rest) = data.split_at(ty.size());
+                *data = rest;
+                macro_rules! decode {
+                    ($t:ty) => {{
+                        let b = bytes.try_into().unwrap();
+                        if *little_endian {
+                            <$t>::from_le_bytes(b) as f64
+                        } else {
+                            <$t>::from_be_bytes(b) as f64
+                        }
+                    }};
+                }
+                Ok(match ty {
+                    Scalar::I8 => decode!(i8),
+                    Scalar::U8 => decode!(u8),
+                    Scalar::I16 => decode!(i16),
+                    Scalar::U16 => decode!(u16),
+                    Scalar::I32 => decode!(i32),
+                    Scalar::U32 => decode!(u32),
+                    Scalar::F32 => decode!(f32),
+                    Scalar::F64 => decode!(f64),
+                })
+            }
+        }
+    }
+}
+
+/// Convert a face index read as a float, rejecting negative & fractional ones.
+fn vertex_index(x: f64) -> io::Result<usize> {
+    if x >= 0.0 && x.fract() == 0.0 {
+        Ok(x as usize)
+    } else {
+        Err(invalid_data(format!("Invalid PLY vertex index {}.", x)))
+    }
+}
+
+/// Parse the header, returning the format, the elements & the offset of the body.
+fn parse_header(data: &[u8]) -> io::Result<(Format, Vec<Element>, usize)> {
+    const END: &[u8] = b"end_header";
+    let end = data
+        .windows(END.len())
+        .position(|w| w == END)
+        .ok_or_else(|| invalid_data("Missing PLY end_header.".to_string()))?;
+    // the body starts after the line break ending the header
+    let body = match data[end..].iter().position(|b| *b == b'\n') {
+        Some(i) => end + i + 1,
+        None => data.len(),
+    };
+    let header = std::str::from_utf8(&data[..end])
+        .map_err(|_| invalid_data("PLY header is not text.".to_string()))?;
+    let mut lines = header.lines().map(str::trim);
+    if lines.next() != Some("ply") {
+        return Err(invalid_data("Not a PLY file.".to_string()));
+    }
+    let mut format = None;
+    let mut elements: Vec<Element> = vec![];
+    for line in lines {
+        let words: Vec<&str> = line.split_whitespace().collect();
+        match words.as_slice() {
+            ["format", f, _] => {
+                format = Some(match *f {
+                    "ascii" => Format::Ascii,
+                    "binary_little_endian" => Format::BinaryLittleEndian,
+                    "binary_big_endian" => Format::BinaryBigEndian,
+                    _ => return Err(invalid_data(format!("Unknown PLY format {}.", f))),
+                })
+            }
+            ["element", name, count] => elements.push(Element {
+                name: name.to_string(),
+                count: count
+                    .parse()
+                    .map_err(|_| invalid_data(format!("Invalid element count {}.", count)))?,
+                properties: vec![],
+            }),
+            ["property", "list", count_ty, item_ty, name] => elements
+                .last_mut()
+                .ok_or_else(|| invalid_data("PLY property before element.".to_string()))?
+                .properties
+                .push(Property {
+                    name: name.to_string(),
+                    ty: PropertyType::List(
+                        Scalar::from_name(count_ty)?,
+                        Scalar::from_name(item_ty)?,
+                    ),
+                }),
+            ["property", ty, name] => elements
+                .last_mut()
+                .ok_or_else(|| invalid_data("PLY property before element.".to_string()))?
+                .properties
+                .push(Property {
+                    name: name.to_string(),
+                    ty: PropertyType::Scalar(Scalar::from_name(ty)?),
+                }),
+            ["comment", ..] | ["obj_info", ..] | [] => {}
+            _ => return Err(invalid_data(format!("Invalid PLY header line {}.", line))),
+        }
+    }
+    let format = format.ok_or_else(|| invalid_data("Missing PLY format.".to_string()))?;
+    Ok((format, elements, body))
+}
+
+/// read_ply: Read a triangle mesh from a PLY file
+pub fn read_ply(filename: &str) -> io::Result<TriangleMesh> {
+    let data = read(filename)?;
+    let (format, elements, offset) = parse_header(&data)?;
+    let mut body = match format {
+        Format::Ascii => Body::Ascii(
+            std::str::from_utf8(&data[offset..])
+                .map_err(|_| invalid_data("PLY data is not text.".to_string()))?
+                .split_ascii_whitespace(),
+        ),
+        Format::BinaryLittleEndian | Format::BinaryBigEndian => Body::Binary {
+            data: &data[offset..],
+            little_endian: format == Format::BinaryLittleEndian,
+        },
+    };
+
+    let mut mesh = TriangleMesh::default();
+    for element in &elements {
+        let find = |names: &[&str]| {
+            element
+                .properties
+                .iter()
+                .position(|p| names.contains(&p.name.as_str()))
+        };
+        let position = [find(&["x"]), find(&["y"]), find(&["z"])];
+        let normal = [find(&["nx"]), find(&["ny"]), find(&["nz"])];
+        let color = [
+            find(&["red", "r"]),
+            find(&["green", "g"]),
+            find(&["blue", "b"]),
+        ];
+        let uv = [
+            find(&["u", "s", "texture_u", "texture_s"]),
+            find(&["v", "t", "texture_v", "texture_t"]),
+        ];
+        let indices = find(&["vertex_indices", "vertex_index"]);
+        let is_vertex = element.name == "vertex";
+        if is_vertex && position.iter().any(Option::is_none) {
+            return Err(invalid_data("PLY vertices without positions.".to_string()));
+        }
+        let has_normals = is_vertex && normal.iter().all(Option::is_some);
+        let has_colors = is_vertex && color.iter().all(Option::is_some);
+        let has_uvs = is_vertex && uv.iter().all(Option::is_some);
+        // colors stored as integers are in [0, 255]
+        let color_scale = match color[0].map(|i| &element.properties[i].ty) {
+            Some(PropertyType::Scalar(Scalar::F32)) | Some(PropertyType::Scalar(Scalar::F64)) => {
+                1.0
+            }
+            _ => 1.0 / 255.0,
+        };
+
+        let mut values = vec![0.0; element.properties.len()];
+        let mut list = vec![];
+        for _ in 0..element.count {
+            for (i, p) in element.properties.iter().enumerate() {
+                match p.ty {
+                    PropertyType::Scalar(ty) => values[i] = body.read(ty)?,
+                    PropertyType::List(count_ty, item_ty) => {
+                        let n = body.read(count_ty)? as usize;
+                        let items = (0..n)
+                            .map(|_| body.read(item_ty))
+                            .collect::<io::Result<Vec<f64>>>()?;
+                        if Some(i) == indices {
+                            list = items;
+                        }
+                    }
+                }
+            }
+            let get = |i: Option<usize>| values[i.unwrap()];
+            if is_vertex {
+                mesh.positions.push(Vec3::new(
+                    get(position[0]),
+                    get(position[1]),
+                    get(position[2]),
+                ));
+                if has_normals {
+                    mesh.normals.get_or_insert_with(Vec::new).push(Vec3::new(
+                        get(normal[0]),
+                        get(normal[1]),
+                        get(normal[2]),
+                    ));
+                }
+                if has_colors {
+                    mesh.colors.get_or_insert_with(Vec::new).push(Color::new(
+                        (get(color[0]) * color_scale) as f32,
+                        (get(color[1]) * color_scale) as f32,
+                        (get(color[2]) * color_scale) as f32,
+                    ));
+                }
+                if has_uvs {
+                    mesh.uvs
+                        .get_or_insert_with(Vec::new)
+                        .push((get(uv[0]), get(uv[1])));
+                }
+            } else if element.name == "face" && list.len() >= 3 {
+                let indices = list
+                    .iter()
+                    .map(|&x| vertex_index(x))
+                    .collect::<io::Result<Vec<usize>>>()?;
+                for k in 1..indices.len() - 1 {
+                    mesh.faces.push([indices[0], indices[k], indices[k + 1]]);
+                }
+            }
+        }
+    }
+    mesh.validate()?;
+    if mesh.normals.is_none() {
+        mesh.compute_normals();
+    }
+    Ok(mesh)
+}
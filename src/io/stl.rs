@@ -0,0 +1,77 @@
+//! stl: read triangle meshes from STL files, in ASCII or binary format.
+//!
+//! STL stores every triangle with its own vertices, so the mesh has no shared vertices nor
+//! attributes. Facet normals are ignored, the vertex order gives the facing.
+
+use std::convert::TryInto;
+use std::fs::read;
+use std::io;
+
+use crate::io::mesh::{invalid_data, TriangleMesh};
+use crate::utils::Vec3;
+
+/// Size of the binary header & triangle count.
+const HEADER_SIZE: usize = 84;
+/// Size of a binary triangle: normal, 3 vertices & attribute byte count.
+const TRIANGLE_SIZE: usize = 50;
+
+/// read_stl: Read a triangle mesh from an STL file
+///
+/// A file is binary if its size matches the triangle count of a binary header, since ASCII files
+/// may start with `solid` in either format.
+pub fn read_stl(filename: &str) -> io::Result<TriangleMesh> {
+    let data = read(filename)?;
+    let mut mesh = TriangleMesh::default();
+    if data.len() >= HEADER_SIZE {
+        let count = u32::from_le_bytes(data[80..84].try_into().unwrap()) as usize;
+        if data.len() == HEADER_SIZE + count * TRIANGLE_SIZE {
+            for tri in data[HEADER_SIZE..].chunks_exact(TRIANGLE_SIZE) {
+                let float =
+                    |i: usize| f32::from_le_bytes(tri[4 * i..4 * i + 4].try_into().unwrap()) as f64;
+                // skip the normal in the first 3 floats
+                for v in 1..4 {
+                    mesh.positions.push(Vec3::new(
+                        float(3 * v),
+                        float(3 * v + 1),
+                        float(3 * v + 2),
+                    ));
+                }
+            }
+            add_faces(&mut mesh);
+            return Ok(mesh);
+        }
+    }
+
+    let content = std::str::from_utf8(&data)
+        .map_err(|_| invalid_data("STL file is neither binary nor text.".to_string()))?;
+    let mut tokens = content.split_whitespace();
+    while let Some(t) = tokens.next() {
+        if t != "vertex" {
+            continue;
+        }
+        let mut coordinate = || -> io::Result<f64> {
+            let t = tokens
+                .next()
+                .ok_or_else(|| invalid_data("Unexpected end of STL file.".to_string()))?;
+            t.parse()
+                .map_err(|_| invalid_data(format!("Invalid STL coordinate {}.", t)))
+        };
+        let (x, y, z) = (coordinate()?, coordinate()?, coordinate()?);
+        mesh.positions.push(Vec3::new(x, y, z));
+    }
+    if mesh.positions.len() % 3 != 0 {
+        return Err(invalid_data(format!(
+            "STL vertex count {} is not a multiple of 3.",
+            mesh.positions.len()
+        )));
+    }
+    add_faces(&mut mesh);
+    Ok(mesh)
+}
+
+/// Make a face of every 3 consecutive vertices.
+fn add_faces(mesh: &mut TriangleMesh) {
+    mesh.faces = (0..mesh.positions.len() / 3)
+        .map(|i| [3 * i, 3 * i + 1, 3 * i + 2])
+        .collect();
+}
@@ -0,0 +1,57 @@
+//! volume: read voxel grids for participating media.
+//!
+//! The file format is plain text: a header with the grid resolution `nx ny nz`, followed by
+//! `nx * ny * nz` whitespace-separated values with x varying fastest, then y, then z.
+
+use std::fs::read_to_string;
+use std::io;
+
+pub struct VoxelGrid {
+    pub nx: usize,
+    pub ny: usize,
+    pub nz: usize,
+    pub data: Vec<f32>,
+}
+
+impl VoxelGrid {
+    #[inline]
+    pub fn at(&self, x: usize, y: usize, z: usize) -> f32 {
+        self.data[(z * self.ny + y) * self.nx + x]
+    }
+}
+
+fn invalid_data(msg: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg)
+}
+
+/// read_voxel_grid: Read a voxel grid from a text file
+pub fn read_voxel_grid(filename: &str) -> io::Result<VoxelGrid> {
+    let content = read_to_string(filename)?;
+    let mut tokens = content.split_whitespace();
+    let mut dims = [0_usize; 3];
+    for d in dims.iter_mut() {
+        let t = tokens
+            .next()
+            .ok_or_else(|| invalid_data("Missing grid resolution.".to_string()))?;
+        *d = t
+            .parse()
+            .map_err(|_| invalid_data(format!("Invalid grid resolution {}.", t)))?;
+    }
+    let data = tokens
+        .map(|t| {
+            t.parse::<f32>()
+                .map_err(|_| invalid_data(format!("Invalid voxel value {}.", t)))
+        })
+        .collect::<io::Result<Vec<f32>>>()?;
+    let [nx, ny, nz] = dims;
+    if nx == 0 || ny == 0 || nz == 0 || data.len() != nx * ny * nz {
+        return Err(invalid_data(format!(
+            "Expected {} * {} * {} voxels, got {}.",
+            nx,
+            ny,
+            nz,
+            data.len()
+        )));
+    }
+    Ok(VoxelGrid { nx, ny, nz, data })
+}
@@ -1,29 +1,125 @@
 extern crate clap;
 
-use clap::{App, Arg};
+use std::sync::Arc;
 
+use clap::{App, Arg, ArgMatches};
+
+use ray_tracer::config::animation::AnimationScene;
+use ray_tracer::config::bokeh::BokehScene;
+use ray_tracer::config::bump::BumpScene;
 use ray_tracer::config::cornell_box::CornellBoxScene;
+use ray_tracer::config::cornell_smoke::CornellSmokeScene;
+use ray_tracer::config::cutout::CutoutScene;
+use ray_tracer::config::mesh::MeshScene;
 use ray_tracer::config::next_week_final_scene::NextWeekFinalScene;
 use ray_tracer::config::random_spheres::RandomSpheresScene;
 use ray_tracer::config::random_spheres_night::RandomSpheresNightScene;
+use ray_tracer::config::shapes::ShapesScene;
+use ray_tracer::config::terrain::TerrainScene;
+use ray_tracer::config::texture_filter::TextureFilterScene;
 use ray_tracer::config::two_spheres::TwoSpheresScene;
 use ray_tracer::config::SceneConfig;
-use ray_tracer::io::file::write_to_png;
-use ray_tracer::render::multi_renderer::PresetLevel;
+use ray_tracer::io::file::{read_picture, write_to_png};
+use ray_tracer::io::lens::read_lens_description;
+use ray_tracer::render::aov::Aov;
+use ray_tracer::render::camera::{
+    make_camera, CameraFrame, CameraObject, EquirectangularCamera, FisheyeCamera,
+    FisheyeProjection, OrthographicCamera,
+};
+use ray_tracer::render::denoiser::DenoiseParams;
+use ray_tracer::render::filter::OutlierRejectionFilter;
+use ray_tracer::render::lens::{ApertureMask, ApertureShape, LensSystemCamera};
+use ray_tracer::render::multi_renderer::{CropOutput, PresetLevel, RenderRegion};
+use ray_tracer::render::reconstruction::{PixelFilter, ReconstructionFilter};
+use ray_tracer::render::stereo::{StereoLayout, StereoRig};
 use ray_tracer::render::MultiRenderer;
-use ray_tracer::render::Renderer;
 
 fn get_configuration(name: &str) -> Box<dyn SceneConfig> {
     match name {
+        "AnimationScene" => Box::new(AnimationScene {}),
+        "BokehScene" => Box::new(BokehScene {}),
+        "BumpScene" => Box::new(BumpScene {}),
         "CornellBoxScene" => Box::new(CornellBoxScene {}),
+        "CornellSmokeScene" => Box::new(CornellSmokeScene {}),
+        "CutoutScene" => Box::new(CutoutScene {}),
+        "MeshScene" => Box::new(MeshScene {}),
         "NextWeekFinalScene" => Box::new(NextWeekFinalScene {}),
         "RandomSpheresScene" => Box::new(RandomSpheresScene { bounce: true }),
         "RandomSpheresNightScene" => Box::new(RandomSpheresNightScene { bounce: false }),
+        "ShapesScene" => Box::new(ShapesScene {}),
+        "TerrainScene" => Box::new(TerrainScene {}),
+        "TextureFilterScene" => Box::new(TextureFilterScene {}),
         "TwoSpheresScene" => Box::new(TwoSpheresScene {}),
         _ => panic!("Invalid scene configuration name {}", name),
     }
 }
 
+/// Parse a comma-separated list of exactly `n` numbers, e.g. "10,20".
+fn parse_numbers(s: &str, n: usize) -> Vec<usize> {
+    let v: Vec<usize> = s
+        .split(',')
+        .map(|x| {
+            x.trim()
+                .parse()
+                .unwrap_or_else(|_| panic!("Invalid number {}", x))
+        })
+        .collect();
+    if v.len() != n {
+        panic!("Expected {} comma-separated numbers, got {}", n, s);
+    }
+    v
+}
+
+/// Parse the value of an optional argument, falling back to `default`.
+fn parse_or<T: std::str::FromStr>(value: Option<&str>, default: T) -> T {
+    match value {
+        Some(v) => v
+            .parse()
+            .unwrap_or_else(|_| panic!("Invalid argument value {}", v)),
+        None => default,
+    }
+}
+
+/// Build the lens system camera from the command line.
+fn make_lens_camera(matches: &ArgMatches, frame: CameraFrame, aspect: f64) -> CameraObject {
+    let file = matches.value_of("lens").unwrap();
+    let elements = read_lens_description(file)
+        .unwrap_or_else(|e| panic!("Failed to read lens description {}: {}", file, e));
+    let mut camera = LensSystemCamera::new(
+        frame,
+        elements,
+        parse_or(matches.value_of("film-diagonal"), 35.0),
+        aspect,
+        parse_or(matches.value_of("focus-distance"), 0.0),
+        parse_or(matches.value_of("lens-unit"), 1.0),
+    )
+    .unwrap();
+    if let Some(d) = matches.value_of("stop-diameter") {
+        camera.set_stop_diameter(parse_or(Some(d), 0.0)).unwrap();
+    }
+    if let Some(b) = matches.value_of("aperture-blades") {
+        camera.set_stop_shape(ApertureShape::polygon(
+            parse_or(Some(b), 0),
+            parse_or(matches.value_of("aperture-rotation"), 0.0),
+        ));
+    }
+    if let Some(m) = matches.value_of("aperture-mask") {
+        let mask = ApertureMask::from_picture(&read_picture(m)).unwrap();
+        camera.set_stop_shape(ApertureShape::Mask(Arc::new(mask)));
+    }
+    make_camera(camera)
+}
+
+/// Build the stereo rig around the perspective camera of the scene.
+fn make_stereo_rig(matches: &ArgMatches, camera: &CameraObject) -> StereoRig {
+    let center = camera
+        .as_perspective()
+        .expect("Stereo rendering needs a perspective camera.");
+    let convergence = parse_or(matches.value_of("convergence"), center.get_focus_distance());
+    let interocular = parse_or(matches.value_of("interocular"), convergence / 30.0);
+    StereoRig::around(center, interocular, convergence)
+}
+
 fn main() {
     let matches = App::new("RustyRay ray-tracing renderer")
         .version("1.0")
@@ -45,6 +141,292 @@ fn main() {
                 .help("The preset to use, among 0~3 standing for low, medium, high & ultra.")
                 .takes_value(true),
         )
+        .arg(
+            Arg::with_name("camera")
+                .long("camera")
+                .value_name("CAMERA")
+                .help(
+                    "Projection to view the scene through from the same pose, among perspective, \
+                     orthographic, fisheye, equisolid, equirectangular & lens. Perspective by \
+                     default.",
+                )
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("fov")
+                .long("fov")
+                .value_name("DEGREES")
+                .help("Field of view of the fisheye cameras, 180 by default.")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("ortho-height")
+                .long("ortho-height")
+                .value_name("HEIGHT")
+                .required_if("camera", "orthographic")
+                .help("Height of the view of the orthographic camera in world units.")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("lens")
+                .long("lens")
+                .value_name("FILE")
+                .required_if("camera", "lens")
+                .help("Lens description traced by the lens camera, e.g. assets/lenses/dgauss.50mm.dat.")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("focus-distance")
+                .long("focus-distance")
+                .value_name("DIST")
+                .required_if("camera", "lens")
+                .help("Distance the lens camera focuses at, in scene units.")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("lens-unit")
+                .long("lens-unit")
+                .value_name("SIZE")
+                .help("Size of a unit of the lens description in scene units, 1 by default.")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("film-diagonal")
+                .long("film-diagonal")
+                .value_name("SIZE")
+                .help("Film diagonal of the lens camera in lens units, 35 by default.")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("stop-diameter")
+                .long("stop-diameter")
+                .value_name("SIZE")
+                .help("Stop the lens camera down to this aperture diameter in lens units.")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("aperture-blades")
+                .long("aperture-blades")
+                .value_name("N")
+                .help("Shape the aperture stop of the lens camera as a polygon of N blades.")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("aperture-rotation")
+                .long("aperture-rotation")
+                .value_name("DEGREES")
+                .requires("aperture-blades")
+                .help("Rotation of the bladed aperture, 0 by default.")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("aperture-mask")
+                .long("aperture-mask")
+                .value_name("FILE")
+                .conflicts_with("aperture-blades")
+                .help("Shape the aperture stop of the lens camera by a grayscale image.")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("stereo")
+                .long("stereo")
+                .value_name("LAYOUT")
+                .help(
+                    "Render left & right eye views around the perspective camera, packed \
+                     side-by-side or top-bottom.",
+                )
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("interocular")
+                .long("interocular")
+                .value_name("DIST")
+                .requires("stereo")
+                .help("Distance between the eyes, 1/30 of the convergence distance by default.")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("convergence")
+                .long("convergence")
+                .value_name("DIST")
+                .requires("stereo")
+                .help("Distance of the zero parallax plane, the focus distance by default.")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("frames")
+                .long("frames")
+                .value_name("FIRST,LAST")
+                .conflicts_with_all(&["stereo", "debug-pixel"])
+                .help("Render the frames FIRST to LAST of the animation to out.NNNN.png.")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("fps")
+                .long("fps")
+                .value_name("FPS")
+                .requires("frames")
+                .help("Frame rate of the animation, 24 by default.")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("shutter")
+                .long("shutter")
+                .value_name("FRACTION")
+                .requires("frames")
+                .help("Fraction of the frame duration the shutter stays open, 0.5 by default.")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("depth")
+                .long("depth")
+                .value_name("DEPTH")
+                .help("Maximum number of bounces of a path, overriding the preset.")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("russian-roulette")
+                .long("russian-roulette")
+                .value_name("MIN_DEPTH")
+                .help("Terminate paths randomly by their throughput after MIN_DEPTH bounces.")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("filter")
+                .long("filter")
+                .value_name("FILTER")
+                .help(
+                    "Pixel reconstruction filter, among box, tent, gaussian, mitchell & lanczos. \
+                     Box by default.",
+                )
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("filter-radius")
+                .long("filter-radius")
+                .value_name("RADIUS")
+                .requires("filter")
+                .help("Radius of the reconstruction filter in pixels.")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("clamp")
+                .long("clamp")
+                .value_name("MAX")
+                .help("Clamp the radiance of every sample to MAX. Biased, off by default.")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("clamp-indirect")
+                .long("clamp-indirect")
+                .value_name("MAX")
+                .help("Clamp the radiance gathered after the first bounce to MAX. Biased, off by default.")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("reject-outliers")
+                .long("reject-outliers")
+                .help("Replace pixels much brighter than their neighborhood. Biased, off by default."),
+        )
+        .arg(
+            Arg::with_name("outlier-threshold")
+                .long("outlier-threshold")
+                .value_name("K")
+                .requires("reject-outliers")
+                .help("Standard deviations above the neighborhood mean an outlier has, 3 by default.")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("outlier-radius")
+                .long("outlier-radius")
+                .value_name("RADIUS")
+                .requires("reject-outliers")
+                .help("Radius of the neighborhood outliers are compared against, 1 by default.")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("crop")
+                .long("crop")
+                .value_name("X0,Y0,X1,Y1")
+                .help("Only render the pixels in [X0, X1) * [Y0, Y1], counting rows from the top.")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("crop-full")
+                .long("crop-full")
+                .requires("crop")
+                .help("Output the full-size picture with unrendered pixels marked in magenta."),
+        )
+        .arg(
+            Arg::with_name("aov")
+                .long("aov")
+                .value_name("AOVS")
+                .help(
+                    "Comma-separated AOVs to write alongside out.png, among normal, albedo, \
+                     depth, position, material & object_id, or \"all\".",
+                )
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("denoise")
+                .long("denoise")
+                .help("Denoise the picture with an AOV-guided edge-avoiding filter."),
+        )
+        .arg(
+            Arg::with_name("denoise-iterations")
+                .long("denoise-iterations")
+                .value_name("N")
+                .requires("denoise")
+                .help("Number of denoising passes, 5 by default.")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("denoise-sigma-color")
+                .long("denoise-sigma-color")
+                .value_name("SIGMA")
+                .requires("denoise")
+                .help("Color sensitivity of the denoiser, 4.0 by default.")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("denoise-sigma-normal")
+                .long("denoise-sigma-normal")
+                .value_name("SIGMA")
+                .requires("denoise")
+                .help("Normal sensitivity of the denoiser, 0.1 by default.")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("denoise-sigma-depth")
+                .long("denoise-sigma-depth")
+                .value_name("SIGMA")
+                .requires("denoise")
+                .help("Relative depth sensitivity of the denoiser, 0.05 by default.")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("denoise-sigma-albedo")
+                .long("denoise-sigma-albedo")
+                .value_name("SIGMA")
+                .requires("denoise")
+                .help("Albedo sensitivity of the denoiser, 0.3 by default.")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("debug-pixel")
+                .long("debug-pixel")
+                .value_name("X,Y")
+                .help("Trace a single pixel, logging every bounce to stdout instead of rendering.")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("debug-samples")
+                .long("debug-samples")
+                .value_name("SAMPLES")
+                .requires("debug-pixel")
+                .help("Sample count used when tracing the debug pixel, 1 by default.")
+                .takes_value(true),
+        )
         .get_matches();
 
     let config = matches.value_of("config").unwrap_or("CornellBoxScene");
@@ -56,14 +438,162 @@ fn main() {
     // set up the scene & the renderer
     let scene = get_configuration(config);
     let mut r = MultiRenderer::new();
-    r.set_camera(scene.get_camera());
+    let camera = scene.get_camera();
+    let camera = match matches.value_of("camera") {
+        None | Some("perspective") => camera,
+        Some(name) => {
+            let frame = camera.get_frame().clone();
+            let aspect = camera.get_aspect_ratio();
+            let fov = parse_or(matches.value_of("fov"), 180.0);
+            match name {
+                "orthographic" => make_camera(OrthographicCamera::new(
+                    frame,
+                    parse_or(matches.value_of("ortho-height"), 1.0),
+                    aspect,
+                )),
+                "fisheye" => make_camera(FisheyeCamera::new(
+                    frame,
+                    fov,
+                    aspect,
+                    FisheyeProjection::Equidistant,
+                )),
+                "equisolid" => make_camera(FisheyeCamera::new(
+                    frame,
+                    fov,
+                    aspect,
+                    FisheyeProjection::Equisolid,
+                )),
+                "equirectangular" => make_camera(EquirectangularCamera::new(frame)),
+                "lens" => make_lens_camera(&matches, frame, aspect),
+                _ => panic!("Invalid camera name {}", name),
+            }
+        }
+    };
+    r.set_camera(camera.clone());
     r.set_world(scene.get_world());
     r.set_render_preset(preset);
+    if let Some(d) = matches.value_of("depth") {
+        r.set_recursion_depth(parse_or(Some(d), 0));
+    }
+    if let Some(d) = matches.value_of("russian-roulette") {
+        r.set_russian_roulette(Some(parse_or(Some(d), 0)));
+    }
+    if let Some(f) = matches.value_of("filter") {
+        let filter =
+            PixelFilter::from_name(f).unwrap_or_else(|| panic!("Invalid filter name {}", f));
+        let radius = parse_or(matches.value_of("filter-radius"), filter.default_radius());
+        r.set_pixel_filter(ReconstructionFilter::new(filter, radius));
+    }
+    if let Some(c) = matches.value_of("clamp") {
+        r.set_sample_clamp(Some(parse_or(Some(c), 0.0)));
+    }
+    if let Some(c) = matches.value_of("clamp-indirect") {
+        r.set_indirect_clamp(Some(parse_or(Some(c), 0.0)));
+    }
+    if matches.is_present("reject-outliers") {
+        let d = OutlierRejectionFilter::default();
+        r.set_outlier_rejection(Some(OutlierRejectionFilter {
+            radius: parse_or(matches.value_of("outlier-radius"), d.radius),
+            threshold: parse_or(matches.value_of("outlier-threshold"), d.threshold),
+        }));
+    }
+    if let Some(c) = matches.value_of("crop") {
+        let v = parse_numbers(c, 4);
+        r.set_render_region(Some(RenderRegion::new(v[0], v[1], v[2], v[3])));
+        if matches.is_present("crop-full") {
+            r.set_crop_output(CropOutput::Marked);
+        }
+    }
+    if let Some(a) = matches.value_of("aov") {
+        let aovs: Vec<Aov> = if a == "all" {
+            Aov::ALL.to_vec()
+        } else {
+            a.split(',')
+                .map(|x| {
+                    Aov::from_name(x.trim()).unwrap_or_else(|| panic!("Invalid AOV name {}", x))
+                })
+                .collect()
+        };
+        r.set_aovs(&aovs);
+    }
+    if matches.is_present("denoise") {
+        let d = DenoiseParams::default();
+        r.set_denoise(Some(DenoiseParams {
+            iterations: parse_or(matches.value_of("denoise-iterations"), d.iterations),
+            sigma_color: parse_or(matches.value_of("denoise-sigma-color"), d.sigma_color),
+            sigma_normal: parse_or(matches.value_of("denoise-sigma-normal"), d.sigma_normal),
+            sigma_depth: parse_or(matches.value_of("denoise-sigma-depth"), d.sigma_depth),
+            sigma_albedo: parse_or(matches.value_of("denoise-sigma-albedo"), d.sigma_albedo),
+        }));
+    }
+    if let Some(p) = matches.value_of("debug-pixel") {
+        let v = parse_numbers(p, 2);
+        let samples: usize = parse_or(matches.value_of("debug-samples"), 1);
+        r.debug_pixel(v[0], v[1], samples)
+            .unwrap_or_else(|s| panic!("Debugging failed, {}", s));
+        return;
+    }
     // fire it up
     println!("Start rendering scene {}...", config);
-    let p = r
-        .render()
+    if let Some(layout) = matches.value_of("stereo") {
+        let layout = StereoLayout::from_name(layout)
+            .unwrap_or_else(|| panic!("Invalid stereo layout {}", layout));
+        let rig = make_stereo_rig(&matches, &camera);
+        let views = r
+            .render_views(&rig.views())
+            .unwrap_or_else(|s| panic!("Render failed, {}", s));
+        let (left, right) = (&views[0], &views[1]);
+        println!("Writing to out.png...");
+        write_to_png(&layout.combine(&left.0, &right.0), "out.png");
+        for ((aov, l), (_, r)) in left.1.buffers.iter().zip(&right.1.buffers) {
+            let filename = format!("out.{}.png", aov.name());
+            println!("Writing to {}...", filename);
+            let p = layout.combine(&aov.visualize(l), &aov.visualize(r));
+            write_to_png(&p, &filename);
+        }
+        return;
+    }
+    if let Some(f) = matches.value_of("frames") {
+        let v = parse_numbers(f, 2);
+        let fps: f64 = parse_or(matches.value_of("fps"), 24.0);
+        let shutter: f64 = parse_or(matches.value_of("shutter"), 0.5);
+        assert!(
+            v[0] <= v[1],
+            "First frame {} should not be after the last frame {}.",
+            v[0],
+            v[1]
+        );
+        assert!(fps > 0.0, "Frame rate should be positive.");
+        assert!(
+            (0.0..=1.0).contains(&shutter),
+            "Shutter should be in [0, 1]."
+        );
+        for frame in v[0]..=v[1] {
+            let t0 = frame as f64 / fps;
+            r.set_camera(camera.with_shutter(t0, t0 + shutter / fps));
+            println!("Rendering frame {}, time = {:.4}s...", frame, t0);
+            let (p, aovs) = r
+                .render_with_aovs()
+                .unwrap_or_else(|s| panic!("Render failed, {}", s));
+            let filename = format!("out.{:04}.png", frame);
+            println!("Writing to {}...", filename);
+            write_to_png(&p, &filename);
+            for (aov, p) in &aovs.buffers {
+                let filename = format!("out.{:04}.{}.png", frame, aov.name());
+                println!("Writing to {}...", filename);
+                write_to_png(&aov.visualize(p), &filename);
+            }
+        }
+        return;
+    }
+    let (p, aovs) = r
+        .render_with_aovs()
         .unwrap_or_else(|s| panic!("Render failed, {}", s));
     println!("Writing to out.png...");
     write_to_png(&p, "out.png");
+    for (aov, p) in &aovs.buffers {
+        let filename = format!("out.{}.png", aov.name());
+        println!("Writing to {}...", filename);
+        write_to_png(&aov.visualize(p), &filename);
+    }
 }
@@ -9,8 +9,22 @@ pub struct AABB {
 }
 
 impl AABB {
+    /// A box containing all of space, e.g. for objects without a bounding box.
+    pub fn infinite() -> Self {
+        AABB {
+            min: Vec3::new(f64::NEG_INFINITY, f64::NEG_INFINITY, f64::NEG_INFINITY),
+            max: Vec3::new(f64::INFINITY, f64::INFINITY, f64::INFINITY),
+        }
+    }
+
     #[inline(always)]
     pub fn hit(&self, r: &Ray, t_min: f64, t_max: f64) -> bool {
+        self.clip(r, t_min, t_max).is_some()
+    }
+
+    /// Get the part of [`t_min`, `t_max`] where the ray is inside the box.
+    #[inline(always)]
+    pub fn clip(&self, r: &Ray, t_min: f64, t_max: f64) -> Option<(f64, f64)> {
         let mut t_min = t_min;
         let mut t_max = t_max;
         let mut t0;
@@ -27,7 +41,7 @@ impl AABB {
         t_min = t_min.max(t0);
         t_max = t_max.min(t1);
         if t_max <= t_min {
-            return false;
+            return None;
         }
         t0 = (self.min.y - o.y) / d.y;
         t1 = (self.max.y - o.y) / d.y;
@@ -37,7 +51,7 @@ impl AABB {
         t_min = t_min.max(t0);
         t_max = t_max.min(t1);
         if t_max <= t_min {
-            return false;
+            return None;
         }
         t0 = (self.min.z - o.z) / d.z;
         t1 = (self.max.z - o.z) / d.z;
@@ -47,9 +61,9 @@ impl AABB {
         t_min = t_min.max(t0);
         t_max = t_max.min(t1);
         if t_max <= t_min {
-            return false;
+            return None;
         }
-        return true;
+        Some((t_min, t_max))
     }
 
     #[inline(always)]
@@ -66,4 +80,20 @@ impl AABB {
         );
         AABB { min: v0, max: v1 }
     }
+
+    /// Get the overlap of two boxes, which is empty with `min` > `max` if they don't overlap.
+    pub fn intersection(&self, b: &AABB) -> AABB {
+        AABB {
+            min: Vec3::new(
+                self.min.x.max(b.min.x),
+                self.min.y.max(b.min.y),
+                self.min.z.max(b.min.z),
+            ),
+            max: Vec3::new(
+                self.max.x.min(b.max.x),
+                self.max.y.min(b.max.y),
+                self.max.z.min(b.max.z),
+            ),
+        }
+    }
 }
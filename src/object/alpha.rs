@@ -0,0 +1,73 @@
+//! Alpha masks: cut-outs of objects where an opacity texture is transparent, like the gaps
+//! between the leaves & wires of textured quads.
+//!
+//! Transparent hits are skipped inside `hit`, so the ray carries on to whatever lies behind for
+//! camera, scattered & shadow rays alike.
+
+use rand::{thread_rng, Rng};
+
+use crate::object::aabb::AABB;
+use crate::object::{HitRecord, Hittable, HittableObject, TextureObject};
+use crate::utils::{Ray, Vec3};
+
+/// Step past a skipped hit before looking for the next one.
+const EPSILON: f64 = 0.0001;
+
+#[derive(Debug, Copy, Clone)]
+pub enum AlphaMode {
+    /// Opaque where the alpha is at least the threshold, for crisp edges.
+    Threshold(f32),
+    /// Opaque with a probability of the alpha, for soft edges once averaged over samples.
+    Stochastic,
+}
+
+/// An object with holes where the alpha of `mask` is transparent.
+pub struct AlphaMask {
+    pub object: HittableObject,
+    pub mask: TextureObject,
+    pub mode: AlphaMode,
+}
+
+impl AlphaMask {
+    pub fn new(object: &HittableObject, mask: &TextureObject, mode: AlphaMode) -> Self {
+        AlphaMask {
+            object: object.clone(),
+            mask: mask.clone(),
+            mode,
+        }
+    }
+
+    fn opaque(&self, h: &HitRecord) -> bool {
+        let alpha = self.mask.get_alpha(h.u, h.v, h.p);
+        match self.mode {
+            AlphaMode::Threshold(threshold) => alpha >= threshold,
+            AlphaMode::Stochastic => alpha >= 1.0 || thread_rng().gen::<f32>() < alpha,
+        }
+    }
+}
+
+impl Hittable for AlphaMask {
+    fn bounding_box(&self) -> Option<&AABB> {
+        self.object.bounding_box()
+    }
+
+    fn hit(&self, r: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+        let mut t_min = t_min;
+        loop {
+            let h = self.object.hit(r, t_min, t_max)?;
+            if self.opaque(&h) {
+                return Some(h);
+            }
+            t_min = h.t + EPSILON;
+        }
+    }
+
+    /// Sampling as a light ignores the holes.
+    fn pdf_value(&self, origin: Vec3<f64>, direction: Vec3<f64>) -> f64 {
+        self.object.pdf_value(origin, direction)
+    }
+
+    fn random(&self, origin: Vec3<f64>) -> Option<Vec3<f64>> {
+        self.object.random(origin)
+    }
+}
@@ -0,0 +1,137 @@
+//! Objects moved, rotated & scaled over time by keyframes.
+//!
+//! The transform is evaluated at the time of each ray, so rendering with a shutter interval
+//! blurs the motion of any object, not only spheres. The bounding box covers the object during
+//! the whole animation.
+
+use crate::object::aabb::AABB;
+use crate::object::{HitRecord, Hittable, HittableObject};
+use crate::utils::keyframe::{Interpolation, Keyframes};
+use crate::utils::transform::Transform;
+use crate::utils::{Ray, Vec3};
+
+/// Number of samples over the animation used to bound the object.
+const BOUND_SAMPLES: usize = 256;
+
+pub struct AnimatedTransform {
+    pub hittable: HittableObject,
+    translation: Keyframes<Vec3<f64>>,
+    rotation: Keyframes<Vec3<f64>>,
+    scale: Keyframes<f64>,
+    bounding_box: Option<AABB>,
+}
+
+impl AnimatedTransform {
+    /// Animate an object by its translation, Euler angles in degrees (see `Mat3::from_euler`)
+    /// & uniform scale.
+    pub fn new(
+        hittable: &HittableObject,
+        translation: Keyframes<Vec3<f64>>,
+        rotation: Keyframes<Vec3<f64>>,
+        scale: Keyframes<f64>,
+    ) -> Self {
+        let mut t = AnimatedTransform {
+            hittable: hittable.clone(),
+            translation,
+            rotation,
+            scale,
+            bounding_box: None,
+        };
+        t.bounding_box = t.bound();
+        t
+    }
+
+    pub fn transform_at(&self, time: f64) -> Transform {
+        Transform::new(
+            self.translation.at(time),
+            self.rotation.at(time),
+            self.scale.at(time),
+        )
+    }
+
+    /// Blur an object moving linearly over the shutter interval, from the pose at `t0` to the
+    /// pose at `t1`, like `MovingSphere` does. Each pose is a translation, Euler angles in degrees
+    /// & a uniform scale.
+    pub fn motion_blur(
+        hittable: &HittableObject,
+        (t0, t1): (f64, f64),
+        translation: (Vec3<f64>, Vec3<f64>),
+        rotation: (Vec3<f64>, Vec3<f64>),
+        scale: (f64, f64),
+    ) -> Self {
+        let linear = Interpolation::Linear;
+        Self::new(
+            hittable,
+            Keyframes::new(t0, translation.0).add(t1, translation.1, linear),
+            Keyframes::new(t0, rotation.0).add(t1, rotation.1, linear),
+            Keyframes::new(t0, scale.0).add(t1, scale.1, linear),
+        )
+    }
+
+    /// Bound the corners of the bounding box of the object at sampled times, padded by the
+    /// largest distance a corner moves between two samples.
+    fn bound(&self) -> Option<AABB> {
+        let b = self.hittable.bounding_box()?;
+        let corners: Vec<Vec3<f64>> = (0..8)
+            .map(|i| {
+                Vec3::new(
+                    if i & 1 == 0 { b.min.x } else { b.max.x },
+                    if i & 2 == 0 { b.min.y } else { b.max.y },
+                    if i & 4 == 0 { b.min.z } else { b.max.z },
+                )
+            })
+            .collect();
+        let ranges = [
+            self.translation.time_range(),
+            self.rotation.time_range(),
+            self.scale.time_range(),
+        ];
+        let t0 = ranges.iter().fold(f64::INFINITY, |m, r| m.min(r.0));
+        let t1 = ranges.iter().fold(f64::NEG_INFINITY, |m, r| m.max(r.1));
+        let mut min = Vec3::<f64>::one() * f64::INFINITY;
+        let mut max = Vec3::<f64>::one() * f64::NEG_INFINITY;
+        let mut padding: f64 = 0.0;
+        let mut last: Option<Vec<Vec3<f64>>> = None;
+        for i in 0..=BOUND_SAMPLES {
+            let time = t0 + (t1 - t0) * i as f64 / BOUND_SAMPLES as f64;
+            let tr = self.transform_at(time);
+            let points: Vec<Vec3<f64>> = corners.iter().map(|c| tr.point(*c)).collect();
+            for p in &points {
+                min = Vec3::new(min.x.min(p.x), min.y.min(p.y), min.z.min(p.z));
+                max = Vec3::new(max.x.max(p.x), max.y.max(p.y), max.z.max(p.z));
+            }
+            if let Some(l) = &last {
+                for (p, q) in points.iter().zip(l) {
+                    padding = padding.max((*p - *q).length());
+                }
+            }
+            last = Some(points);
+        }
+        Some(AABB {
+            min: min - padding,
+            max: max + padding,
+        })
+    }
+}
+
+impl Hittable for AnimatedTransform {
+    fn bounding_box(&self) -> Option<&AABB> {
+        self.bounding_box.as_ref()
+    }
+
+    fn hit(&self, r: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+        let tr = self.transform_at(r.t);
+        // the ray parameter is the same in both spaces
+        let nr = Ray {
+            orig: tr.inverse_point(r.orig),
+            dir: tr.inverse_vector(r.dir),
+            t: r.t,
+        };
+        let mut record = self.hittable.hit(&nr, t_min, t_max)?;
+        record.p = tr.point(record.p);
+        record.normal = tr.normal(record.normal);
+        record.dpdu = tr.vector(record.dpdu);
+        record.dpdv = tr.vector(record.dpdv);
+        Some(record)
+    }
+}
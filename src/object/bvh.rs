@@ -1,10 +1,7 @@
 //! BVH: Binary Volume Hierarchy
 
-use std::cmp::Ordering;
 use std::sync::Arc;
 
-use rand::{thread_rng, Rng};
-
 use crate::object::aabb::AABB;
 use crate::object::{HitRecord, Hittable, HittableObject};
 use crate::utils::Ray;
@@ -24,10 +21,7 @@ impl BVHNode {
                 BVHNode {
                     left: hittable_list[0].clone(),
                     right: hittable_list[0].clone(),
-                    bounding_box: hittable_list[0]
-                        .bounding_box()
-                        .expect("HittableObject not valid for AABB")
-                        .clone(),
+                    bounding_box: BVHNode::box_of(&hittable_list[0]),
                 }
             }
             _ => BVHNode::build_tree(hittable_list),
@@ -36,9 +30,10 @@ impl BVHNode {
 
     /// Build the binary volume hierarchy for a hittable list length greater than 1
     fn build_tree(hittable_list: &mut [HittableObject]) -> BVHNode {
-        // get the comparator and sort the list by it
-        let comp = BVHNode::get_comparator();
-        hittable_list.sort_by(comp);
+        // sort the list along the axis the objects are spread the most, then split it in half
+        let axis = BVHNode::widest_axis(hittable_list);
+        hittable_list
+            .sort_by(|a, b| BVHNode::min_along(a, axis).total_cmp(&BVHNode::min_along(b, axis)));
         let (s1, s2) = hittable_list.split_at_mut(hittable_list.len() / 2);
         let left = if s1.len() == 1 {
             Arc::clone(&s1[0])
@@ -50,14 +45,7 @@ impl BVHNode {
         } else {
             Arc::new(BVHNode::build_tree(s2))
         };
-        let bounding_box = left
-            .bounding_box()
-            .expect("HittableObject not valid for AABB.")
-            .union(
-                right
-                    .bounding_box()
-                    .expect("HittableObject not valid for AABB."),
-            );
+        let bounding_box = BVHNode::box_of(&left).union(&BVHNode::box_of(&right));
         BVHNode {
             left,
             right,
@@ -65,34 +53,45 @@ impl BVHNode {
         }
     }
 
-    fn get_comparator() -> fn(&HittableObject, &HittableObject) -> Ordering {
-        match thread_rng().gen_range(0, 3) {
-            0 => |a: &HittableObject, b: &HittableObject| {
-                a.bounding_box()
-                    .unwrap()
-                    .min
-                    .x
-                    .partial_cmp(&b.bounding_box().unwrap().min.x)
-                    .unwrap()
-            },
-            1 => |a: &HittableObject, b: &HittableObject| {
-                a.bounding_box()
-                    .unwrap()
-                    .min
-                    .y
-                    .partial_cmp(&b.bounding_box().unwrap().min.y)
-                    .unwrap()
-            },
-            _ => |a: &HittableObject, b: &HittableObject| {
-                a.bounding_box()
-                    .unwrap()
-                    .min
-                    .z
-                    .partial_cmp(&b.bounding_box().unwrap().min.z)
-                    .unwrap()
-            },
+    /// Get the bounding box of an object, where objects without one are treated as infinite.
+    fn box_of(h: &HittableObject) -> AABB {
+        h.bounding_box().cloned().unwrap_or_else(AABB::infinite)
+    }
+
+    /// Get the lowest coordinate of an object along `axis`, where objects without a bounding box
+    /// are treated as infinite.
+    fn min_along(h: &HittableObject, axis: usize) -> f64 {
+        match (h.bounding_box(), axis) {
+            (Some(b), 0) => b.min.x,
+            (Some(b), 1) => b.min.y,
+            (Some(b), _) => b.min.z,
+            (None, _) => f64::NEG_INFINITY,
         }
     }
+
+    /// Get the axis along which the corners of the bounding boxes are spread the most.
+    fn widest_axis(hittable_list: &[HittableObject]) -> usize {
+        let mut lo = [f64::INFINITY; 3];
+        let mut hi = [f64::NEG_INFINITY; 3];
+        for h in hittable_list {
+            for axis in 0..3 {
+                // objects without a bounding box don't tell where the others are
+                let x = BVHNode::min_along(h, axis);
+                if x.is_finite() {
+                    lo[axis] = lo[axis].min(x);
+                    hi[axis] = hi[axis].max(x);
+                }
+            }
+        }
+        let extent = |axis: usize| hi[axis] - lo[axis];
+        (1..3).fold(0, |best, axis| {
+            if extent(axis) > extent(best) {
+                axis
+            } else {
+                best
+            }
+        })
+    }
 }
 
 impl Hittable for BVHNode {
@@ -4,6 +4,7 @@ use rand::{thread_rng, Rng};
 
 use crate::object::aabb::AABB;
 use crate::object::material::Isotropic;
+use crate::object::phase::{PhaseFunctionObject, PhaseMaterial};
 use crate::object::{Face, HitRecord, Hittable, HittableObject, MaterialObject, TextureObject};
 use crate::utils::{Ray, Vec3};
 
@@ -21,6 +22,23 @@ impl ConstantMedium {
             phase_function: Arc::new(Isotropic { albedo: t.clone() }),
         }
     }
+
+    /// Create a medium scattering by the given phase function instead of isotropically.
+    pub fn with_phase_function(
+        boundary: &HittableObject,
+        d: f64,
+        t: &TextureObject,
+        phase: &PhaseFunctionObject,
+    ) -> Self {
+        ConstantMedium {
+            boundary: boundary.clone(),
+            neg_inv_density: -1.0 / d,
+            phase_function: Arc::new(PhaseMaterial {
+                albedo: t.clone(),
+                phase: phase.clone(),
+            }),
+        }
+    }
 }
 
 impl Hittable for ConstantMedium {
@@ -33,11 +51,8 @@ impl Hittable for ConstantMedium {
         let debugging: bool = ENABLE_DEBUGGING && thread_rng().gen::<f64>() < 0.0001;
 
         // hit twice to calc enter & exit distance
-        if let Some(rec1) = self
-            .boundary
-            .hit(r, std::f64::NEG_INFINITY, std::f64::INFINITY)
-        {
-            if let Some(rec2) = self.boundary.hit(r, rec1.t + 0.0001, std::f64::INFINITY) {
+        if let Some(rec1) = self.boundary.hit(r, f64::NEG_INFINITY, f64::INFINITY) {
+            if let Some(rec2) = self.boundary.hit(r, rec1.t + 0.0001, f64::INFINITY) {
                 if debugging {
                     println!("CONSTANT MEDIUM HIT, rec1.t={}, rec2.t={}", rec1.t, rec2.t)
                 }
@@ -61,7 +76,11 @@ impl Hittable for ConstantMedium {
                     u: 0.0,
                     v: 0.0,
                     normal: Vec3::new(1.0, 0.0, 0.0),
+                    dpdu: Vec3::zero(),
+                    dpdv: Vec3::zero(),
                     mat: self.phase_function.clone(),
+                    footprint: 0.0,
+                    object_id: 0,
                 };
                 if debugging {
                     println!(
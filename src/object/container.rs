@@ -3,16 +3,28 @@ use crate::object::bvh::BVHNode;
 use crate::object::{HitRecord, Hittable, HittableObject};
 use crate::utils::Ray;
 
+/// A list of hittables, accelerated by a BVH.
+///
+/// Objects without a bounding box, like infinite planes, can't be put in the BVH, so they are
+/// kept in a separate list & tested one by one.
 pub struct Container {
     hittables: Vec<HittableObject>,
+    unbounded: Vec<HittableObject>,
     bounding_box: Option<AABB>,
     bvh: Option<BVHNode>,
 }
 
+impl Default for Container {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl Container {
     pub fn new() -> Self {
         Container {
             hittables: vec![],
+            unbounded: vec![],
             bounding_box: None,
             bvh: None,
         }
@@ -27,23 +39,30 @@ impl Container {
     }
 
     pub fn update_metadata(&mut self) {
-        if self.hittables.is_empty() {
+        let (mut bounded, unbounded): (Vec<_>, Vec<_>) = self
+            .hittables
+            .iter()
+            .cloned()
+            .partition(|obj| obj.bounding_box().is_some());
+        self.unbounded = unbounded;
+        if bounded.is_empty() {
             self.bvh = None;
             self.bounding_box = None;
             return;
         }
-        self.bvh = Some(BVHNode::new(&mut self.hittables));
-        let mut aabb = AABB::default();
-        for obj in &self.hittables {
-            if let Some(b2) = obj.bounding_box() {
-                aabb = aabb.union(b2);
-            }
-        }
-        self.bounding_box = Some(aabb);
+        let bvh = BVHNode::new(&mut bounded);
+        // the container is only bounded if everything inside is
+        self.bounding_box = if self.unbounded.is_empty() {
+            Some(bvh.bounding_box.clone())
+        } else {
+            None
+        };
+        self.bvh = Some(bvh);
     }
 
     pub fn clear(&mut self) {
         self.hittables.clear();
+        self.unbounded.clear();
         self.bvh = None;
         self.bounding_box = None;
     }
@@ -55,9 +74,16 @@ impl Hittable for Container {
     }
 
     fn hit(&self, r: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
-        match &self.bvh {
+        let mut rec = match &self.bvh {
             Some(b) => b.hit(r, t_min, t_max),
             None => None,
+        };
+        for obj in &self.unbounded {
+            let closest = rec.as_ref().map_or(t_max, |h| h.t);
+            if let Some(h) = obj.hit(r, t_min, closest) {
+                rec = Some(h);
+            }
         }
+        rec
     }
 }
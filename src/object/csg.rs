@@ -0,0 +1,139 @@
+//! CSG: Constructive Solid Geometry, combining two closed objects into one.
+//!
+//! The ray is walked through the entry & exit hits of both objects, keeping track of whether it
+//! is inside each of them, & the first hit where it goes in or out of the combination is
+//! reported.
+
+use crate::object::aabb::AABB;
+use crate::object::{Face, HitRecord, Hittable, HittableObject};
+use crate::utils::Ray;
+
+/// Distance to step past a hit before looking for the next one.
+const EPSILON: f64 = 0.0001;
+
+#[derive(Debug, Copy, Clone)]
+pub enum CsgOperation {
+    /// Inside either object.
+    Union,
+    /// Inside both objects.
+    Intersection,
+    /// Inside the first object but not the second.
+    Difference,
+}
+
+impl CsgOperation {
+    #[inline(always)]
+    fn inside(&self, in_a: bool, in_b: bool) -> bool {
+        match self {
+            CsgOperation::Union => in_a || in_b,
+            CsgOperation::Intersection => in_a && in_b,
+            CsgOperation::Difference => in_a && !in_b,
+        }
+    }
+}
+
+/// Combination of two closed objects, like spheres, cubes or other CSG objects.
+///
+/// Objects without a bounding box work as long as their surface splits space in two, e.g. an
+/// infinite plane for the half space behind it.
+pub struct Csg {
+    pub a: HittableObject,
+    pub b: HittableObject,
+    pub operation: CsgOperation,
+    bounding_box: Option<AABB>,
+}
+
+impl Csg {
+    pub fn new(a: &HittableObject, b: &HittableObject, operation: CsgOperation) -> Self {
+        let (box_a, box_b) = (a.bounding_box(), b.bounding_box());
+        let bounding_box = match operation {
+            CsgOperation::Union => match (box_a, box_b) {
+                (Some(x), Some(y)) => Some(x.union(y)),
+                _ => None,
+            },
+            CsgOperation::Intersection => match (box_a, box_b) {
+                (Some(x), Some(y)) => Some(x.intersection(y)),
+                (x, y) => x.or(y).cloned(),
+            },
+            CsgOperation::Difference => box_a.cloned(),
+        };
+        Csg {
+            a: a.clone(),
+            b: b.clone(),
+            operation,
+            bounding_box,
+        }
+    }
+
+    pub fn union(a: &HittableObject, b: &HittableObject) -> Self {
+        Self::new(a, b, CsgOperation::Union)
+    }
+
+    pub fn intersection(a: &HittableObject, b: &HittableObject) -> Self {
+        Self::new(a, b, CsgOperation::Intersection)
+    }
+
+    pub fn difference(a: &HittableObject, b: &HittableObject) -> Self {
+        Self::new(a, b, CsgOperation::Difference)
+    }
+}
+
+impl Hittable for Csg {
+    fn bounding_box(&self) -> Option<&AABB> {
+        self.bounding_box.as_ref()
+    }
+
+    fn hit(&self, r: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+        // the children are queried without `t_max`, so their first hits tell whether the ray
+        // starts inside them: a closed surface is left before it is entered
+        let mut hit_a = self.a.hit(r, t_min, f64::INFINITY);
+        let mut hit_b = self.b.hit(r, t_min, f64::INFINITY);
+        let leaving = |h: &Option<HitRecord>| {
+            matches!(
+                h,
+                Some(HitRecord {
+                    f: Face::Outward,
+                    ..
+                })
+            )
+        };
+        let mut in_a = leaving(&hit_a);
+        let mut in_b = leaving(&hit_b);
+        let mut inside = self.operation.inside(in_a, in_b);
+
+        loop {
+            let a_first = match (&hit_a, &hit_b) {
+                (Some(x), Some(y)) => x.t <= y.t,
+                (Some(_), None) => true,
+                (None, Some(_)) => false,
+                (None, None) => return None,
+            };
+            let rec = if a_first {
+                in_a = !in_a;
+                hit_a.take()
+            } else {
+                in_b = !in_b;
+                hit_b.take()
+            }?;
+            if rec.t > t_max {
+                return None;
+            }
+            let now_inside = self.operation.inside(in_a, in_b);
+            if now_inside != inside {
+                // the facing follows the combination, which flips it for subtracted surfaces
+                let f = if now_inside {
+                    Face::Inward
+                } else {
+                    Face::Outward
+                };
+                return Some(HitRecord { f, ..rec });
+            }
+            inside = now_inside;
+            if a_first {
+                hit_a = self.a.hit(r, rec.t + EPSILON, f64::INFINITY);
+            } else {
+                hit_b = self.b.hit(r, rec.t + EPSILON, f64::INFINITY);
+            }
+        }
+    }
+}
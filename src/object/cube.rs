@@ -2,12 +2,14 @@ use std::sync::Arc;
 
 use crate::object::aabb::AABB;
 use crate::object::bvh::BVHNode;
-use crate::object::rect::{XYRect, XZRect, YZRect};
+use crate::object::quad::Quad;
 use crate::object::{HitRecord, Hittable, HittableObject, MaterialObject};
 use crate::utils::{Ray, Vec3};
 
+/// An axis-aligned box from `p0` to `p1`, made of six quads facing outwards.
 pub struct Cube {
     pub sides: [HittableObject; 6],
+    /// Material of the cube, or of its -x face for cubes with a material per face.
     pub mat: MaterialObject,
     bvh: Arc<BVHNode>,
     bounding_box: AABB,
@@ -15,19 +17,39 @@ pub struct Cube {
 
 impl Cube {
     pub fn new(p0: Vec3<f64>, p1: Vec3<f64>, mat: &MaterialObject) -> Self {
+        Self::with_face_materials(
+            p0,
+            p1,
+            [
+                mat.clone(),
+                mat.clone(),
+                mat.clone(),
+                mat.clone(),
+                mat.clone(),
+                mat.clone(),
+            ],
+        )
+    }
+
+    /// Create a cube with a material per face, in the order -x, +x, -y, +y, -z, +z.
+    pub fn with_face_materials(p0: Vec3<f64>, p1: Vec3<f64>, mats: [MaterialObject; 6]) -> Self {
         assert!(p0.x <= p1.x && p0.y <= p1.y && p0.z <= p1.z);
+        let d = p1 - p0;
+        let dx = Vec3::new(d.x, 0.0, 0.0);
+        let dy = Vec3::new(0.0, d.y, 0.0);
+        let dz = Vec3::new(0.0, 0.0, d.z);
         let mut sides: [HittableObject; 6] = [
-            Arc::new(XYRect::new((p0.x, p0.y), (p1.x, p1.y), p0.z, mat)),
-            Arc::new(XYRect::new((p0.x, p0.y), (p1.x, p1.y), p1.z, mat)),
-            Arc::new(XZRect::new((p0.x, p0.z), (p1.x, p1.z), p0.y, mat)),
-            Arc::new(XZRect::new((p0.x, p0.z), (p1.x, p1.z), p1.y, mat)),
-            Arc::new(YZRect::new((p0.y, p0.z), (p1.y, p1.z), p0.x, mat)),
-            Arc::new(YZRect::new((p0.y, p0.z), (p1.y, p1.z), p1.x, mat)),
+            Arc::new(Quad::new(p0, dz, dy, &mats[0])),
+            Arc::new(Quad::new(p0 + dx, dy, dz, &mats[1])),
+            Arc::new(Quad::new(p0, dx, dz, &mats[2])),
+            Arc::new(Quad::new(p0 + dy, dz, dx, &mats[3])),
+            Arc::new(Quad::new(p0, dy, dx, &mats[4])),
+            Arc::new(Quad::new(p0 + dz, dx, dy, &mats[5])),
         ];
         let bvh = Arc::new(BVHNode::new(&mut sides));
         Cube {
             sides,
-            mat: mat.clone(),
+            mat: mats[0].clone(),
             bvh,
             bounding_box: AABB { min: p0, max: p1 },
         }
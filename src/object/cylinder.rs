@@ -0,0 +1,349 @@
+//! Primitives around an axis segment: cylinders, cones & capsules.
+//!
+//! Each one is intersected in its own space, where the axis starts at the origin & runs along
+//! y, with `u` going around the axis in turns & `v` along it.
+
+use std::f64::consts::PI;
+
+use crate::object::aabb::AABB;
+use crate::object::{Face, HitRecord, Hittable, MaterialObject};
+use crate::utils::poly::solve_quadratic;
+use crate::utils::transform::Transform;
+use crate::utils::{Ray, Vec3};
+
+/// Extent along each world axis of a disk of `radius` perpendicular to the unit vector `axis`.
+pub(crate) fn disk_extent(axis: Vec3<f64>, radius: f64) -> Vec3<f64> {
+    Vec3::new(
+        radius * (1.0 - axis.x * axis.x).max(0.0).sqrt(),
+        radius * (1.0 - axis.y * axis.y).max(0.0).sqrt(),
+        radius * (1.0 - axis.z * axis.z).max(0.0).sqrt(),
+    )
+}
+
+/// Smallest box containing all `points`.
+pub(crate) fn bounding_points(points: &[Vec3<f64>]) -> AABB {
+    let mut min = Vec3::new(f64::INFINITY, f64::INFINITY, f64::INFINITY);
+    let mut max = -min;
+    for p in points {
+        min = Vec3::new(min.x.min(p.x), min.y.min(p.y), min.z.min(p.z));
+        max = Vec3::new(max.x.max(p.x), max.y.max(p.y), max.z.max(p.z));
+    }
+    AABB { min, max }
+}
+
+/// The angle of (`x`, `z`) around the y axis in turns, in [0, 1).
+#[inline(always)]
+pub(crate) fn turns(x: f64, z: f64) -> f64 {
+    z.atan2(x).rem_euclid(2.0 * PI) / (2.0 * PI)
+}
+
+/// Closest hit found so far in object space: `t`, normal, `u`, `v` & the derivative of the point
+/// along `v`.
+pub(crate) type LocalHit = (f64, Vec3<f64>, f64, f64, Vec3<f64>);
+
+/// Turn a hit in object space into a `HitRecord`.
+pub(crate) fn to_record(
+    frame: &Transform,
+    r: &Ray,
+    hit: LocalHit,
+    mat: &MaterialObject,
+) -> HitRecord {
+    let (t, normal, u, v, dpdv) = hit;
+    let mut normal = frame.normal(normal);
+    let f = Face::calc(&normal, r);
+    if let Face::Outward = f {
+        normal = -normal;
+    }
+    let p = r.at(t);
+    // u is always the angle around the axis
+    let local = frame.inverse_point(p);
+    let dpdu = Vec3::new(-local.z, 0.0, local.x) * (2.0 * PI);
+    HitRecord {
+        f,
+        t,
+        p,
+        u,
+        v,
+        normal,
+        dpdu: frame.vector(dpdu),
+        dpdv: frame.vector(dpdv),
+        mat: mat.clone(),
+        footprint: 0.0,
+        object_id: 0,
+    }
+}
+
+/// Hit the caps of height `y` & `radius`, facing `ny` along the axis.
+fn hit_cap(
+    o: Vec3<f64>,
+    d: Vec3<f64>,
+    y: f64,
+    ny: f64,
+    radius: f64,
+    t_min: f64,
+    t_max: f64,
+) -> Option<LocalHit> {
+    if d.y == 0.0 {
+        return None;
+    }
+    let t = (y - o.y) / d.y;
+    if t < t_min || t > t_max {
+        return None;
+    }
+    let (x, z) = (o.x + t * d.x, o.z + t * d.z);
+    let dist_square = x * x + z * z;
+    if dist_square > radius * radius {
+        return None;
+    }
+    let dist = dist_square.sqrt();
+    Some((
+        t,
+        Vec3::new(0.0, ny, 0.0),
+        turns(x, z),
+        dist / radius,
+        Vec3::new(x, 0.0, z) / dist.max(1e-12) * radius,
+    ))
+}
+
+/// Hit the side of the infinite cylinder of `radius` around the y axis, with `y` in
+/// [`y0`, `y1`].
+fn hit_tube(
+    o: Vec3<f64>,
+    d: Vec3<f64>,
+    radius: f64,
+    y0: f64,
+    y1: f64,
+    t_min: f64,
+    t_max: f64,
+) -> Option<(f64, Vec3<f64>)> {
+    let a = d.x * d.x + d.z * d.z;
+    let b = 2.0 * (o.x * d.x + o.z * d.z);
+    let c = o.x * o.x + o.z * o.z - radius * radius;
+    for t in solve_quadratic(a, b, c).into_iter().flatten() {
+        if t < t_min || t > t_max {
+            continue;
+        }
+        let p = o + d * t;
+        if p.y >= y0 && p.y <= y1 {
+            return Some((t, p));
+        }
+    }
+    None
+}
+
+/// A cylinder of `radius` from `base` to `top`, optionally closed by flat caps.
+pub struct Cylinder {
+    pub radius: f64,
+    pub height: f64,
+    pub capped: bool,
+    pub mat: MaterialObject,
+    frame: Transform,
+    bounding_box: AABB,
+}
+
+impl Cylinder {
+    pub fn new(
+        base: Vec3<f64>,
+        top: Vec3<f64>,
+        radius: f64,
+        capped: bool,
+        mat: &MaterialObject,
+    ) -> Self {
+        let axis = top - base;
+        let extent = disk_extent(axis.unit_vector(), radius);
+        Cylinder {
+            radius,
+            height: axis.length(),
+            capped,
+            mat: mat.clone(),
+            frame: Transform::from_axis(base, axis),
+            bounding_box: bounding_points(&[
+                base - extent,
+                base + extent,
+                top - extent,
+                top + extent,
+            ]),
+        }
+    }
+}
+
+impl Hittable for Cylinder {
+    fn bounding_box(&self) -> Option<&AABB> {
+        Some(&self.bounding_box)
+    }
+
+    fn hit(&self, r: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+        let o = self.frame.inverse_point(r.origin());
+        let d = self.frame.inverse_vector(r.direction());
+        let mut t_max = t_max;
+        let mut closest = None;
+        if let Some((t, p)) = hit_tube(o, d, self.radius, 0.0, self.height, t_min, t_max) {
+            let normal = Vec3::new(p.x, 0.0, p.z) / self.radius;
+            closest = Some((
+                t,
+                normal,
+                turns(p.x, p.z),
+                p.y / self.height,
+                Vec3::new(0.0, self.height, 0.0),
+            ));
+            t_max = t;
+        }
+        if self.capped {
+            for &(y, ny) in &[(0.0, -1.0), (self.height, 1.0)] {
+                if let Some(h) = hit_cap(o, d, y, ny, self.radius, t_min, t_max) {
+                    t_max = h.0;
+                    closest = Some(h);
+                }
+            }
+        }
+        closest.map(|h| to_record(&self.frame, r, h, &self.mat))
+    }
+}
+
+/// A cone with a base of `radius` at `base` & its tip at `apex`, optionally closed at the base.
+pub struct Cone {
+    pub radius: f64,
+    pub height: f64,
+    pub capped: bool,
+    pub mat: MaterialObject,
+    frame: Transform,
+    bounding_box: AABB,
+}
+
+impl Cone {
+    pub fn new(
+        base: Vec3<f64>,
+        apex: Vec3<f64>,
+        radius: f64,
+        capped: bool,
+        mat: &MaterialObject,
+    ) -> Self {
+        let axis = apex - base;
+        let extent = disk_extent(axis.unit_vector(), radius);
+        Cone {
+            radius,
+            height: axis.length(),
+            capped,
+            mat: mat.clone(),
+            frame: Transform::from_axis(base, axis),
+            bounding_box: bounding_points(&[base - extent, base + extent, apex]),
+        }
+    }
+}
+
+impl Hittable for Cone {
+    fn bounding_box(&self) -> Option<&AABB> {
+        Some(&self.bounding_box)
+    }
+
+    /// Solve x^2 + z^2 = k^2 (h - y)^2 with k = radius / height.
+    fn hit(&self, r: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+        let o = self.frame.inverse_point(r.origin());
+        let d = self.frame.inverse_vector(r.direction());
+        let h = self.height;
+        let k2 = (self.radius / h).powi(2);
+        let oy = h - o.y;
+        let a = d.x * d.x + d.z * d.z - k2 * d.y * d.y;
+        let b = 2.0 * (o.x * d.x + o.z * d.z + k2 * oy * d.y);
+        let c = o.x * o.x + o.z * o.z - k2 * oy * oy;
+
+        let mut t_max = t_max;
+        let mut closest = None;
+        for t in solve_quadratic(a, b, c).into_iter().flatten() {
+            if t < t_min || t > t_max {
+                continue;
+            }
+            let p = o + d * t;
+            // the equation also describes the mirrored cone above the apex
+            if p.y >= 0.0 && p.y <= h {
+                let n = Vec3::new(p.x, k2 * (h - p.y), p.z);
+                let normal = if n.length_square() > 0.0 {
+                    n.unit_vector()
+                } else {
+                    Vec3::new(0.0, 1.0, 0.0)
+                };
+                // the point slides towards the apex as v grows
+                let rho = (p.x * p.x + p.z * p.z).sqrt().max(1e-12);
+                let dpdv = Vec3::new(-p.x / rho * self.radius, h, -p.z / rho * self.radius);
+                closest = Some((t, normal, turns(p.x, p.z), p.y / h, dpdv));
+                t_max = t;
+                break;
+            }
+        }
+        if self.capped {
+            if let Some(h) = hit_cap(o, d, 0.0, -1.0, self.radius, t_min, t_max) {
+                closest = Some(h);
+            }
+        }
+        closest.map(|h| to_record(&self.frame, r, h, &self.mat))
+    }
+}
+
+/// All points within `radius` of the segment from `p0` to `p1`: a cylinder with hemispherical
+/// ends.
+pub struct Capsule {
+    pub radius: f64,
+    pub height: f64,
+    pub mat: MaterialObject,
+    frame: Transform,
+    bounding_box: AABB,
+}
+
+impl Capsule {
+    pub fn new(p0: Vec3<f64>, p1: Vec3<f64>, radius: f64, mat: &MaterialObject) -> Self {
+        Capsule {
+            radius,
+            height: (p1 - p0).length(),
+            mat: mat.clone(),
+            frame: Transform::from_axis(p0, p1 - p0),
+            bounding_box: bounding_points(&[p0 - radius, p0 + radius, p1 - radius, p1 + radius]),
+        }
+    }
+}
+
+impl Hittable for Capsule {
+    fn bounding_box(&self) -> Option<&AABB> {
+        Some(&self.bounding_box)
+    }
+
+    fn hit(&self, r: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+        let o = self.frame.inverse_point(r.origin());
+        let d = self.frame.inverse_vector(r.direction());
+        let (h, radius) = (self.height, self.radius);
+        // v runs along the whole length, including the ends
+        let length = h + 2.0 * radius;
+        let v = |y: f64| (y + radius) / length;
+        // along the surface, in the direction of the axis
+        let dpdv = |n: Vec3<f64>| Vec3::new(0.0, length, 0.0) - n * (n.y * length);
+
+        let mut t_max = t_max;
+        let mut closest = None;
+        if let Some((t, p)) = hit_tube(o, d, radius, 0.0, h, t_min, t_max) {
+            let normal = Vec3::new(p.x, 0.0, p.z) / radius;
+            closest = Some((t, normal, turns(p.x, p.z), v(p.y), dpdv(normal)));
+            t_max = t;
+        }
+        // only the outer half of each end sphere is on the surface
+        for &(cy, outward) in &[(0.0, -1.0), (h, 1.0)] {
+            let oc = o - Vec3::new(0.0, cy, 0.0);
+            let roots = solve_quadratic(
+                d.length_square(),
+                2.0 * oc.dot(d),
+                oc.length_square() - radius * radius,
+            );
+            for t in roots.into_iter().flatten() {
+                if t < t_min || t > t_max {
+                    continue;
+                }
+                let p = o + d * t;
+                if (p.y - cy) * outward >= 0.0 {
+                    let normal = (p - Vec3::new(0.0, cy, 0.0)) / radius;
+                    closest = Some((t, normal, turns(p.x, p.z), v(p.y), dpdv(normal)));
+                    t_max = t;
+                    break;
+                }
+            }
+        }
+        closest.map(|h| to_record(&self.frame, r, h, &self.mat))
+    }
+}
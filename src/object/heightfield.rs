@@ -0,0 +1,265 @@
+//! Heightfield: terrain from a grid of heights, e.g. a grayscale picture or noise.
+//!
+//! Every cell of the grid is split into two triangles. Rays walk through the cells they cross
+//! with a 2D DDA, skipping cells whose height range they pass over or under.
+
+use crate::object::aabb::AABB;
+use crate::object::triangle::{hit_triangle, smooth_normal};
+use crate::object::{Face, HitRecord, Hittable, MaterialObject};
+use crate::render::filter::luminance;
+use crate::utils::perlin::Perlin;
+use crate::utils::{Picture, Ray, Vec3};
+
+/// Octaves of noise summed up by `Heightfield::from_perlin`.
+const NOISE_OCTAVES: usize = 6;
+
+/// A grid of `nx` x `nz` height samples over the rectangle from `corner` spanning `size` along
+/// x & z, with heights measured upwards from `corner`.
+///
+/// `u` runs along x & `v` along z, so a picture used for the heights can texture it too.
+pub struct Heightfield {
+    pub nx: usize,
+    pub nz: usize,
+    pub corner: Vec3<f64>,
+    pub size: (f64, f64),
+    pub mat: MaterialObject,
+    heights: Vec<f64>,
+    normals: Vec<Vec3<f64>>,
+    /// Height range of every cell, for skipping cells quickly.
+    cell_ranges: Vec<(f64, f64)>,
+    bounding_box: AABB,
+}
+
+impl Heightfield {
+    /// Create a heightfield from `heights` in rows of constant z, starting from the lowest z.
+    pub fn new(
+        heights: Vec<f64>,
+        (nx, nz): (usize, usize),
+        corner: Vec3<f64>,
+        size: (f64, f64),
+        mat: &MaterialObject,
+    ) -> Self {
+        assert!(
+            nx >= 2 && nz >= 2,
+            "Heightfield needs at least 2x2 samples."
+        );
+        assert_eq!(heights.len(), nx * nz, "Heightfield sample count mismatch.");
+        let (dx, dz) = (size.0 / (nx - 1) as f64, size.1 / (nz - 1) as f64);
+        let h = |i: usize, j: usize| heights[j * nx + i];
+
+        // smooth normals from the slopes around each sample
+        let mut normals = Vec::with_capacity(nx * nz);
+        for j in 0..nz {
+            for i in 0..nx {
+                let (i0, i1) = (i.saturating_sub(1), (i + 1).min(nx - 1));
+                let (j0, j1) = (j.saturating_sub(1), (j + 1).min(nz - 1));
+                let slope_x = (h(i1, j) - h(i0, j)) / ((i1 - i0) as f64 * dx);
+                let slope_z = (h(i, j1) - h(i, j0)) / ((j1 - j0) as f64 * dz);
+                normals.push(Vec3::new(-slope_x, 1.0, -slope_z).unit_vector());
+            }
+        }
+
+        let mut cell_ranges = Vec::with_capacity((nx - 1) * (nz - 1));
+        for j in 0..nz - 1 {
+            for i in 0..nx - 1 {
+                let corners = [h(i, j), h(i + 1, j), h(i, j + 1), h(i + 1, j + 1)];
+                let lo = corners.iter().cloned().fold(f64::INFINITY, f64::min);
+                let hi = corners.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
+                cell_ranges.push((corner.y + lo, corner.y + hi));
+            }
+        }
+
+        let lo = heights.iter().cloned().fold(f64::INFINITY, f64::min);
+        let hi = heights.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
+        let bounding_box = AABB {
+            min: Vec3::new(corner.x, corner.y + lo - 0.001, corner.z),
+            max: Vec3::new(corner.x + size.0, corner.y + hi + 0.001, corner.z + size.1),
+        };
+        Heightfield {
+            nx,
+            nz,
+            corner,
+            size,
+            mat: mat.clone(),
+            heights,
+            normals,
+            cell_ranges,
+            bounding_box,
+        }
+    }
+
+    /// Create a heightfield from the brightness of a picture, one sample per pixel, with white
+    /// at `height`. The top of the picture is at the far end of z, like an image texture.
+    pub fn from_picture(
+        picture: &Picture,
+        corner: Vec3<f64>,
+        size: (f64, f64),
+        height: f64,
+        mat: &MaterialObject,
+    ) -> Self {
+        let (nx, nz) = (picture.width, picture.height);
+        let mut heights = Vec::with_capacity(nx * nz);
+        for j in 0..nz {
+            for i in 0..nx {
+                heights.push(luminance(&picture.at(i, nz - 1 - j)) as f64 * height);
+            }
+        }
+        Self::new(heights, (nx, nz), corner, size, mat)
+    }
+
+    /// Create a heightfield from fractal Perlin noise, with `samples` along x & z, `frequency`
+    /// in features per world unit & heights from 0 to `height`.
+    pub fn from_perlin(
+        generator: &Perlin,
+        frequency: f64,
+        samples: (usize, usize),
+        corner: Vec3<f64>,
+        size: (f64, f64),
+        height: f64,
+        mat: &MaterialObject,
+    ) -> Self {
+        let (nx, nz) = samples;
+        let mut heights = Vec::with_capacity(nx * nz);
+        for j in 0..nz {
+            for i in 0..nx {
+                // noise is only defined for positive coordinates
+                let p = Vec3::new(
+                    i as f64 / (nx - 1) as f64 * size.0,
+                    0.5,
+                    j as f64 / (nz - 1) as f64 * size.1,
+                );
+                let (mut sum, mut weight, mut total, mut f) = (0.0, 1.0, 0.0, frequency);
+                for _ in 0..NOISE_OCTAVES {
+                    sum += weight * generator.smoothed_shifted_noise(p, f) as f64;
+                    total += weight;
+                    weight *= 0.5;
+                    f *= 2.0;
+                }
+                heights.push(sum / total);
+            }
+        }
+        // noise stays close to its mean, so stretch it to the full range
+        let lo = heights.iter().cloned().fold(f64::INFINITY, f64::min);
+        let hi = heights.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
+        let scale = if hi > lo { height / (hi - lo) } else { 0.0 };
+        let heights = heights.into_iter().map(|h| (h - lo) * scale).collect();
+        Self::new(heights, samples, corner, size, mat)
+    }
+
+    #[inline(always)]
+    fn vertex(&self, i: usize, j: usize) -> Vec3<f64> {
+        Vec3::new(
+            self.corner.x + self.size.0 * i as f64 / (self.nx - 1) as f64,
+            self.corner.y + self.heights[j * self.nx + i],
+            self.corner.z + self.size.1 * j as f64 / (self.nz - 1) as f64,
+        )
+    }
+
+    /// Hit the two triangles of cell (`i`, `j`).
+    fn hit_cell(&self, r: &Ray, i: usize, j: usize, t_min: f64, t_max: f64) -> Option<HitRecord> {
+        let corners = [(i, j), (i + 1, j), (i + 1, j + 1), (i, j + 1)];
+        let mut t_max = t_max;
+        let mut closest = None;
+        for tri in &[[0, 1, 2], [0, 2, 3]] {
+            let [a, b, c] = [corners[tri[0]], corners[tri[1]], corners[tri[2]]];
+            let (p0, p1, p2) = (
+                self.vertex(a.0, a.1),
+                self.vertex(b.0, b.1),
+                self.vertex(c.0, c.1),
+            );
+            if let Some((t, b1, b2)) = hit_triangle(r, p0, p1, p2, t_min, t_max) {
+                let n = |(i, j): (usize, usize)| self.normals[j * self.nx + i];
+                let smooth = n(a) * (1.0 - b1 - b2) + n(b) * b1 + n(c) * b2;
+                let geometric = (p2 - p0).cross(p1 - p0);
+                closest = Some((t, geometric, smooth_normal(smooth, geometric)));
+                t_max = t;
+            }
+        }
+        let (t, geometric, mut normal) = closest?;
+        // the facing follows the actual surface, the shading normal is flipped to match it
+        let f = Face::calc(&geometric, r);
+        if let Face::Outward = f {
+            normal = -normal;
+        }
+        let p = r.at(t);
+        // u & v follow x & z, climbing the slope of the triangle
+        let slope = |n: f64| -n / geometric.y;
+        Some(HitRecord {
+            f,
+            t,
+            p,
+            u: ((p.x - self.corner.x) / self.size.0).clamp(0.0, 1.0),
+            v: ((p.z - self.corner.z) / self.size.1).clamp(0.0, 1.0),
+            normal,
+            dpdu: Vec3::new(1.0, slope(geometric.x), 0.0) * self.size.0,
+            dpdv: Vec3::new(0.0, slope(geometric.z), 1.0) * self.size.1,
+            mat: self.mat.clone(),
+            footprint: 0.0,
+            object_id: 0,
+        })
+    }
+}
+
+impl Hittable for Heightfield {
+    fn bounding_box(&self) -> Option<&AABB> {
+        Some(&self.bounding_box)
+    }
+
+    fn hit(&self, r: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+        let (t_enter, t_exit) = self.bounding_box.clip(r, t_min, t_max)?;
+        let (o, d) = (r.origin(), r.direction());
+        let (cells_x, cells_z) = (self.nx - 1, self.nz - 1);
+        let (dx, dz) = (self.size.0 / cells_x as f64, self.size.1 / cells_z as f64);
+
+        // cell containing the entry point
+        let start = r.at(t_enter);
+        let cell = |x: f64, size: f64, n: usize| ((x / size).floor().max(0.0) as usize).min(n - 1);
+        let mut i = cell(start.x - self.corner.x, dx, cells_x);
+        let mut j = cell(start.z - self.corner.z, dz, cells_z);
+
+        // ray parameter of the next cell border & the step between borders, along each axis
+        let setup = |o: f64, d: f64, index: usize, size: f64, origin: f64| {
+            if d > 0.0 {
+                ((origin + (index + 1) as f64 * size - o) / d, size / d)
+            } else if d < 0.0 {
+                ((origin + index as f64 * size - o) / d, -size / d)
+            } else {
+                (f64::INFINITY, f64::INFINITY)
+            }
+        };
+        let (mut next_x, delta_x) = setup(o.x, d.x, i, dx, self.corner.x);
+        let (mut next_z, delta_z) = setup(o.z, d.z, j, dz, self.corner.z);
+
+        let mut t = t_enter;
+        while t <= t_exit {
+            let t_out = next_x.min(next_z).min(t_exit);
+            // skip the cell if the ray is above or below all of it
+            let (lo, hi) = self.cell_ranges[j * cells_x + i];
+            let (y0, y1) = (o.y + d.y * t, o.y + d.y * t_out);
+            if y0.min(y1) <= hi && y0.max(y1) >= lo {
+                if let Some(rec) = self.hit_cell(r, i, j, t_min, t_max) {
+                    return Some(rec);
+                }
+            }
+            if t_out >= t_exit {
+                break;
+            }
+            if next_x < next_z {
+                if (d.x > 0.0 && i + 1 >= cells_x) || (d.x < 0.0 && i == 0) {
+                    break;
+                }
+                i = if d.x > 0.0 { i + 1 } else { i - 1 };
+                t = next_x;
+                next_x += delta_x;
+            } else {
+                if (d.z > 0.0 && j + 1 >= cells_z) || (d.z < 0.0 && j == 0) {
+                    break;
+                }
+                j = if d.z > 0.0 { j + 1 } else { j - 1 };
+                t = next_z;
+                next_z += delta_z;
+            }
+        }
+        None
+    }
+}
@@ -0,0 +1,244 @@
+//! Heterogeneous participating media, driven by a density field.
+//!
+//! Free paths are sampled with delta tracking against the maximum density of the field. A real
+//! collision either scatters the ray by the phase function, or absorbs it, emitting light if the
+//! medium is emissive.
+
+use std::sync::Arc;
+
+use rand::{thread_rng, Rng};
+
+use crate::io::volume::VoxelGrid;
+use crate::object::aabb::AABB;
+use crate::object::material::{FilteredRay, Isotropic};
+use crate::object::phase::{PhaseFunctionObject, PhaseMaterial};
+use crate::object::{
+    Face, HitRecord, Hittable, HittableObject, Material, MaterialObject, TextureObject,
+};
+use crate::utils::perlin::Perlin;
+use crate::utils::{Color, Ray, Vec3};
+
+pub trait DensityField {
+    fn density(&self, p: Vec3<f64>) -> f64;
+    /// An upper bound of `density` over the whole field, used as the majorant for tracking.
+    fn max_density(&self) -> f64;
+}
+
+/// Thread-safe, read-only objects that implement `DensityField` trait
+pub type DensityFieldObject = Arc<dyn DensityField + Send + Sync>;
+
+/// Density from a voxel grid stretched over an axis-aligned box, trilinearly interpolated.
+pub struct GridDensity {
+    grid: VoxelGrid,
+    bounds: AABB,
+    max_density: f64,
+}
+
+impl GridDensity {
+    pub fn new(grid: VoxelGrid, bounds: AABB) -> Self {
+        let max_density = grid.data.iter().fold(0.0_f32, |m, x| m.max(*x)) as f64;
+        GridDensity {
+            grid,
+            bounds,
+            max_density,
+        }
+    }
+}
+
+impl DensityField for GridDensity {
+    fn density(&self, p: Vec3<f64>) -> f64 {
+        let b = &self.bounds;
+        if p.x < b.min.x || p.y < b.min.y || p.z < b.min.z {
+            return 0.0;
+        }
+        if p.x > b.max.x || p.y > b.max.y || p.z > b.max.z {
+            return 0.0;
+        }
+        // voxel centers are at half-integer positions
+        let g = &self.grid;
+        let rel = (p - b.min) / (b.max - b.min);
+        let coord = |r: f64, n: usize| {
+            let x = (r * n as f64 - 0.5).max(0.0).min((n - 1) as f64);
+            let i = (x as usize).min(n.saturating_sub(2));
+            (i, (i + 1).min(n - 1), x - i as f64)
+        };
+        let (x0, x1, fx) = coord(rel.x, g.nx);
+        let (y0, y1, fy) = coord(rel.y, g.ny);
+        let (z0, z1, fz) = coord(rel.z, g.nz);
+        let lerp = |a: f32, b: f32, t: f64| a as f64 * (1.0 - t) + b as f64 * t;
+        let c00 = lerp(g.at(x0, y0, z0), g.at(x1, y0, z0), fx);
+        let c10 = lerp(g.at(x0, y1, z0), g.at(x1, y1, z0), fx);
+        let c01 = lerp(g.at(x0, y0, z1), g.at(x1, y0, z1), fx);
+        let c11 = lerp(g.at(x0, y1, z1), g.at(x1, y1, z1), fx);
+        let c0 = c00 * (1.0 - fy) + c10 * fy;
+        let c1 = c01 * (1.0 - fy) + c11 * fy;
+        c0 * (1.0 - fz) + c1 * fz
+    }
+
+    fn max_density(&self) -> f64 {
+        self.max_density
+    }
+}
+
+/// Procedural density from perlin turbulence.
+///
+/// * `scale`: Frequency of the noise. Larger this value, smaller the puffs.
+/// * `depth`: Octaves of the turbulence.
+/// * `density`: Density multiplier.
+pub struct NoiseDensity {
+    pub generator: Arc<Perlin>,
+    pub scale: f64,
+    pub depth: usize,
+    pub density: f64,
+}
+
+impl DensityField for NoiseDensity {
+    fn density(&self, p: Vec3<f64>) -> f64 {
+        self.density * self.generator.turbulence(p * self.scale, self.depth) as f64
+    }
+
+    fn max_density(&self) -> f64 {
+        // every octave of smoothed noise is in [0, 1], with weights 1, 1/2, 1/4...
+        self.density * (2.0 - 0.5_f64.powi(self.depth as i32 - 1))
+    }
+}
+
+/// Material of an absorption event: the path ends, collecting the emission of the medium.
+struct MediumAbsorption {
+    emission: Option<TextureObject>,
+    brightness: f32,
+}
+
+impl Material for MediumAbsorption {
+    fn get_type(&self) -> &'static str {
+        "MediumAbsorption"
+    }
+
+    fn emit(&self, h: &HitRecord) -> Color {
+        match &self.emission {
+            Some(e) => h.texture_color(e) * self.brightness,
+            None => Color::zero(),
+        }
+    }
+
+    fn scatter(&self, _r: &Ray, _h: &HitRecord) -> Option<FilteredRay> {
+        None
+    }
+}
+
+pub struct HeterogeneousMedium {
+    boundary: HittableObject,
+    density: DensityFieldObject,
+    sigma_s: f64,
+    sigma_a: f64,
+    albedo: TextureObject,
+    phase_function: MaterialObject,
+    absorption: MaterialObject,
+}
+
+impl HeterogeneousMedium {
+    /// Create a purely scattering medium with albedo `t` inside `boundary`.
+    pub fn new(boundary: &HittableObject, density: &DensityFieldObject, t: &TextureObject) -> Self {
+        HeterogeneousMedium {
+            boundary: boundary.clone(),
+            density: density.clone(),
+            sigma_s: 1.0,
+            sigma_a: 0.0,
+            albedo: t.clone(),
+            phase_function: Arc::new(Isotropic { albedo: t.clone() }),
+            absorption: Arc::new(MediumAbsorption {
+                emission: None,
+                brightness: 0.0,
+            }),
+        }
+    }
+
+    /// Set the scattering & absorption coefficients, which scale the density field.
+    pub fn set_coefficients(&mut self, sigma_s: f64, sigma_a: f64) {
+        assert!(sigma_s >= 0.0 && sigma_a >= 0.0 && sigma_s + sigma_a > 0.0);
+        self.sigma_s = sigma_s;
+        self.sigma_a = sigma_a;
+    }
+
+    /// Make absorption events emit `emission * brightness`, e.g. for fire.
+    pub fn set_emission(&mut self, emission: &TextureObject, brightness: f32) {
+        self.absorption = Arc::new(MediumAbsorption {
+            emission: Some(emission.clone()),
+            brightness,
+        });
+    }
+
+    /// Scatter by the given phase function instead of isotropically.
+    pub fn set_phase_function(&mut self, phase: &PhaseFunctionObject) {
+        self.phase_function = Arc::new(PhaseMaterial {
+            albedo: self.albedo.clone(),
+            phase: phase.clone(),
+        });
+    }
+
+    #[inline(always)]
+    fn majorant(&self) -> f64 {
+        (self.sigma_s + self.sigma_a) * self.density.max_density()
+    }
+
+    /// Get the ray parameters where the ray enters & exits the boundary, clipped to the range.
+    fn get_interval(&self, r: &Ray, t_min: f64, t_max: f64) -> Option<(f64, f64)> {
+        let rec1 = self.boundary.hit(r, f64::NEG_INFINITY, f64::INFINITY)?;
+        let rec2 = self.boundary.hit(r, rec1.t + 0.0001, f64::INFINITY)?;
+        let t1 = rec1.t.max(t_min).max(0.0);
+        let t2 = rec2.t.min(t_max);
+        if t1 < t2 {
+            Some((t1, t2))
+        } else {
+            None
+        }
+    }
+}
+
+impl Hittable for HeterogeneousMedium {
+    fn bounding_box(&self) -> Option<&AABB> {
+        self.boundary.bounding_box()
+    }
+
+    fn hit(&self, r: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+        let (t1, t2) = self.get_interval(r, t_min, t_max)?;
+        let majorant = self.majorant();
+        if majorant <= 0.0 {
+            return None;
+        }
+        // delta tracking: sample tentative collisions against the majorant, and accept them
+        // with the ratio of the actual density
+        let mut rng = thread_rng();
+        let step = 1.0 / (majorant * r.dir.length());
+        let mut t = t1;
+        loop {
+            t -= (1.0 - rng.gen::<f64>()).ln() * step;
+            if t >= t2 {
+                return None;
+            }
+            let p = r.at(t);
+            let density = self.density.density(p);
+            let sigma_t = (self.sigma_s + self.sigma_a) * density;
+            if rng.gen::<f64>() * majorant < sigma_t {
+                let mat = if rng.gen::<f64>() * (self.sigma_s + self.sigma_a) < self.sigma_a {
+                    self.absorption.clone()
+                } else {
+                    self.phase_function.clone()
+                };
+                return Some(HitRecord {
+                    f: Face::Outward,
+                    t,
+                    p,
+                    u: 0.0,
+                    v: 0.0,
+                    normal: Vec3::new(1.0, 0.0, 0.0),
+                    dpdu: Vec3::zero(),
+                    dpdv: Vec3::zero(),
+                    mat,
+                    footprint: 0.0,
+                    object_id: 0,
+                });
+            }
+        }
+    }
+}
@@ -0,0 +1,72 @@
+//! Instancing: many copies of one shared geometry, each placed by its own transform.
+//!
+//! The geometry, e.g. a `Container` with its own BVH, is stored once & every instance only keeps
+//! a reference to it, a transform & an optional material. Put the instances in a `Container` to
+//! get a top-level BVH over them.
+
+use crate::object::aabb::AABB;
+use crate::object::cylinder::bounding_points;
+use crate::object::{HitRecord, Hittable, HittableObject, MaterialObject};
+use crate::utils::transform::Transform;
+use crate::utils::{Ray, Vec3};
+
+pub struct Instance {
+    pub geometry: HittableObject,
+    pub transform: Transform,
+    /// Material replacing the ones of the geometry, if any.
+    pub mat: Option<MaterialObject>,
+    bounding_box: Option<AABB>,
+}
+
+impl Instance {
+    pub fn new(geometry: &HittableObject, transform: Transform) -> Self {
+        let bounding_box = geometry.bounding_box().map(|b| {
+            let corners: Vec<Vec3<f64>> = (0..8)
+                .map(|i| {
+                    transform.point(Vec3::new(
+                        if i & 1 == 0 { b.min.x } else { b.max.x },
+                        if i & 2 == 0 { b.min.y } else { b.max.y },
+                        if i & 4 == 0 { b.min.z } else { b.max.z },
+                    ))
+                })
+                .collect();
+            bounding_points(&corners)
+        });
+        Instance {
+            geometry: geometry.clone(),
+            transform,
+            mat: None,
+            bounding_box,
+        }
+    }
+
+    /// Render this instance with `mat` instead of the materials of the geometry.
+    pub fn set_material(&mut self, mat: &MaterialObject) {
+        self.mat = Some(mat.clone());
+    }
+}
+
+impl Hittable for Instance {
+    fn bounding_box(&self) -> Option<&AABB> {
+        self.bounding_box.as_ref()
+    }
+
+    fn hit(&self, r: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+        let tr = &self.transform;
+        // the ray parameter is the same in both spaces
+        let nr = Ray {
+            orig: tr.inverse_point(r.orig),
+            dir: tr.inverse_vector(r.dir),
+            t: r.t,
+        };
+        let mut record = self.geometry.hit(&nr, t_min, t_max)?;
+        record.p = tr.point(record.p);
+        record.normal = tr.normal(record.normal);
+        record.dpdu = tr.vector(record.dpdu);
+        record.dpdv = tr.vector(record.dpdv);
+        if let Some(mat) = &self.mat {
+            record.mat = mat.clone();
+        }
+        Some(record)
+    }
+}
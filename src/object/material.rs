@@ -1,8 +1,12 @@
 use rand::{thread_rng, Rng};
 
-use crate::object::{Face, HitRecord, TextureObject};
+use crate::object::{Face, HitRecord, MaterialObject, TextureObject};
+use crate::render::filter::luminance;
 use crate::utils::{Color, Ray, Vec3};
 
+/// Distance in world units between the points sampled for bump mapping.
+const BUMP_DELTA: f64 = 1e-3;
+
 pub struct FilteredRay {
     pub attenuation: Color,
     pub scattered: Ray,
@@ -10,10 +14,26 @@ pub struct FilteredRay {
 
 pub trait Material {
     fn get_type(&self) -> &'static str;
-    fn emit(&self, _u: f64, _v: f64, _p: Vec3<f64>) -> Color {
+    fn emit(&self, _h: &HitRecord) -> Color {
+        Color::zero()
+    }
+    /// Base color of the material at hit `h`, used for the albedo AOV.
+    fn albedo(&self, _h: &HitRecord) -> Color {
         Color::zero()
     }
+    /// Normal used for shading hit `h` of ray `r`, facing against the ray like `h.normal`.
+    ///
+    /// Materials perturbing the surface normal, like normal & bump maps, override this.
+    fn shading_normal(&self, _r: &Ray, h: &HitRecord) -> Vec3<f64> {
+        h.normal
+    }
     fn scatter(&self, r: &Ray, h: &HitRecord) -> Option<FilteredRay>;
+    /// Density of `scatter` choosing the direction of `scattered`, in solid angle.
+    ///
+    /// Materials scattering into a single direction, like mirrors & glass, return zero.
+    fn scattering_pdf(&self, _r: &Ray, _h: &HitRecord, _scattered: &Ray) -> f64 {
+        0.0
+    }
 }
 
 pub struct LambertianDiffuse {
@@ -33,9 +53,12 @@ impl Material for LambertianDiffuse {
     fn get_type(&self) -> &'static str {
         "LambertianDiffuse"
     }
+    fn albedo(&self, h: &HitRecord) -> Color {
+        h.texture_color(&self.texture)
+    }
     fn scatter(&self, r: &Ray, h: &HitRecord) -> Option<FilteredRay> {
         Some(FilteredRay {
-            attenuation: self.texture.get_color(h.u, h.v, h.p),
+            attenuation: h.texture_color(&self.texture),
             scattered: Ray {
                 orig: h.p,
                 dir: h.normal + rand_unit_vector(),
@@ -43,6 +66,10 @@ impl Material for LambertianDiffuse {
             },
         })
     }
+    fn scattering_pdf(&self, _r: &Ray, h: &HitRecord, scattered: &Ray) -> f64 {
+        let cosine = h.normal.dot(scattered.dir.unit_vector());
+        cosine.max(0.0) / std::f64::consts::PI
+    }
 }
 
 pub struct Metal {
@@ -52,13 +79,9 @@ pub struct Metal {
 
 impl Metal {
     pub fn new(fuzziness: f64, albedo: Vec3<f32>) -> Metal {
-        if fuzziness > 1.0 {
-            return Metal {
-                fuzziness: 1.0,
-                albedo,
-            };
-        } else {
-            return Metal { fuzziness, albedo };
+        Metal {
+            fuzziness: fuzziness.min(1.0),
+            albedo,
         }
     }
 }
@@ -67,10 +90,13 @@ impl Material for Metal {
     fn get_type(&self) -> &'static str {
         "Metal"
     }
+    fn albedo(&self, _h: &HitRecord) -> Color {
+        self.albedo
+    }
     fn scatter(&self, r: &Ray, h: &HitRecord) -> Option<FilteredRay> {
         let reflect_dir = r.direction() - h.normal * (2.0 * r.direction().dot(h.normal))
             + rand_unit_vector() * self.fuzziness;
-        return if reflect_dir.dot(h.normal) > 0.0 {
+        if reflect_dir.dot(h.normal) > 0.0 {
             Some(FilteredRay {
                 attenuation: self.albedo,
                 scattered: Ray {
@@ -81,7 +107,7 @@ impl Material for Metal {
             })
         } else {
             None
-        };
+        }
     }
 }
 
@@ -115,6 +141,9 @@ impl Material for Dielectric {
     fn get_type(&self) -> &'static str {
         "Dielectric"
     }
+    fn albedo(&self, _h: &HitRecord) -> Color {
+        self.albedo
+    }
     fn scatter(&self, r: &Ray, h: &HitRecord) -> Option<FilteredRay> {
         let er = match h.f {
             Face::Inward => self.eta_inv,
@@ -154,8 +183,12 @@ impl Material for DiffuseLight {
         "DiffuseLight"
     }
 
-    fn emit(&self, u: f64, v: f64, p: Vec3<f64>) -> Color {
-        self.emit.get_color(u, v, p) * self.brightness
+    fn emit(&self, h: &HitRecord) -> Color {
+        h.texture_color(&self.emit) * self.brightness
+    }
+
+    fn albedo(&self, h: &HitRecord) -> Color {
+        h.texture_color(&self.emit)
     }
 
     fn scatter(&self, _r: &Ray, _h: &HitRecord) -> Option<FilteredRay> {
@@ -172,9 +205,13 @@ impl Material for Isotropic {
         "Isotropic"
     }
 
+    fn albedo(&self, h: &HitRecord) -> Color {
+        h.texture_color(&self.albedo)
+    }
+
     fn scatter(&self, r: &Ray, h: &HitRecord) -> Option<FilteredRay> {
         Some(FilteredRay {
-            attenuation: self.albedo.get_color(h.u, h.v, h.p),
+            attenuation: h.texture_color(&self.albedo),
             scattered: Ray {
                 orig: h.p,
                 dir: rand_unit_vector(),
@@ -182,4 +219,163 @@ impl Material for Isotropic {
             },
         })
     }
+
+    fn scattering_pdf(&self, _r: &Ray, _h: &HitRecord, _scattered: &Ray) -> f64 {
+        1.0 / (4.0 * std::f64::consts::PI)
+    }
+}
+
+/// Orthonormal frame at hit `h`: the tangent along `dpdu`, the bitangent on the side of `dpdv` &
+/// the outward normal of the surface. Surfaces without derivatives get an arbitrary tangent.
+fn shading_frame(h: &HitRecord) -> (Vec3<f64>, Vec3<f64>, Vec3<f64>) {
+    let n = match h.f {
+        Face::Inward => h.normal,
+        Face::Outward => -h.normal,
+    };
+    let mut t = h.dpdu - n * n.dot(h.dpdu);
+    if t.length_square() < 1e-20 {
+        let axis = if n.x.abs() < 0.9 {
+            Vec3::new(1.0, 0.0, 0.0)
+        } else {
+            Vec3::new(0.0, 1.0, 0.0)
+        };
+        t = axis - n * n.dot(axis);
+    }
+    let t = t.unit_vector();
+    let b = n.cross(t);
+    let b = if b.dot(h.dpdv) < 0.0 { -b } else { b };
+    (t, b, n)
+}
+
+/// Turn a perturbed outward normal `n` at hit `h` into a shading normal facing against ray `r`.
+///
+/// Perturbed normals may face away from the ray, which would scatter light into the surface,
+/// so they are tilted until they are just visible.
+fn face_against(n: Vec3<f64>, r: &Ray, h: &HitRecord) -> Vec3<f64> {
+    let n = match h.f {
+        Face::Inward => n,
+        Face::Outward => -n,
+    };
+    let d = r.direction().unit_vector();
+    let cosine = n.dot(d);
+    if cosine < 0.0 {
+        n
+    } else {
+        (n - d * (cosine + 0.01)).unit_vector()
+    }
+}
+
+/// Copy of hit `h` with the shading normal of `material`.
+fn shaded(material: &dyn Material, r: &Ray, h: &HitRecord) -> HitRecord {
+    HitRecord {
+        normal: material.shading_normal(r, h),
+        ..h.clone()
+    }
+}
+
+/// Forward every method but `shading_normal` to the wrapped `material`, shading it with the
+/// perturbed normal.
+macro_rules! forward_to_material {
+    () => {
+        fn get_type(&self) -> &'static str {
+            self.material.get_type()
+        }
+        fn emit(&self, h: &HitRecord) -> Color {
+            self.material.emit(h)
+        }
+        fn albedo(&self, h: &HitRecord) -> Color {
+            self.material.albedo(h)
+        }
+        fn scatter(&self, r: &Ray, h: &HitRecord) -> Option<FilteredRay> {
+            self.material.scatter(r, &shaded(self, r, h))
+        }
+        fn scattering_pdf(&self, r: &Ray, h: &HitRecord, scattered: &Ray) -> f64 {
+            self.material
+                .scattering_pdf(r, &shaded(self, r, h), scattered)
+        }
+    };
+}
+
+/// A material whose normals are read from a tangent-space normal map, usually an image.
+///
+/// Colors map to the normal by `2c - 1`, with red along `dpdu`, green along `dpdv` & blue along
+/// the surface normal, so the flat color (0.5, 0.5, 1) leaves the surface unchanged.
+pub struct NormalMap {
+    pub material: MaterialObject,
+    pub map: TextureObject,
+}
+
+impl Material for NormalMap {
+    forward_to_material!();
+
+    fn shading_normal(&self, r: &Ray, h: &HitRecord) -> Vec3<f64> {
+        let (t, b, n) = shading_frame(h);
+        let c = h.texture_color(&self.map).apply(|x| x as f64 * 2.0 - 1.0);
+        let perturbed = t * c.x + b * c.y + n * c.z;
+        if perturbed.length_square() < 1e-20 {
+            return h.normal;
+        }
+        face_against(perturbed.unit_vector(), r, h)
+    }
+}
+
+/// A material with bumps displacing the surface outwards by the luminance of `height` times
+/// `scale`, in world units.
+///
+/// Any texture works, textures of the texture coordinates as well as solid textures of the
+/// position like noise & marble.
+pub struct BumpMap {
+    pub material: MaterialObject,
+    pub height: TextureObject,
+    pub scale: f64,
+}
+
+impl BumpMap {
+    /// Height at hit `h` moved by `du`, `dv` & `dp`, filtered over the footprint of `h`.
+    fn height_at(&self, h: &HitRecord, du: f64, dv: f64, dp: Vec3<f64>) -> f64 {
+        let moved = HitRecord {
+            u: h.u + du,
+            v: h.v + dv,
+            p: h.p + dp,
+            ..h.clone()
+        };
+        luminance(&moved.texture_color(&self.height)) as f64 * self.scale
+    }
+}
+
+impl Material for BumpMap {
+    forward_to_material!();
+
+    /// Differentiate the displaced surface along `dpdu` & `dpdv`, stepping the texture
+    /// coordinates along with the position.
+    fn shading_normal(&self, r: &Ray, h: &HitRecord) -> Vec3<f64> {
+        let (t, b, n) = shading_frame(h);
+        // a step of BUMP_DELTA on the surface along `derivative`, or along `fallback` if the
+        // surface has no texture coordinates, & the matching change of u or v
+        let step = |derivative: Vec3<f64>, fallback: Vec3<f64>| {
+            let d = derivative - n * n.dot(derivative);
+            let len = d.length();
+            if len > 1e-12 {
+                (d * (BUMP_DELTA / len), BUMP_DELTA / derivative.length())
+            } else {
+                (fallback * BUMP_DELTA, 0.0)
+            }
+        };
+        let (step_u, du) = step(h.dpdu, t);
+        let (step_v, dv) = step(h.dpdv, b);
+        let h0 = self.height_at(h, 0.0, 0.0, Vec3::zero());
+        let hu = self.height_at(h, du, 0.0, step_u);
+        let hv = self.height_at(h, 0.0, dv, step_v);
+        let perturbed = (step_u + n * (hu - h0)).cross(step_v + n * (hv - h0));
+        if perturbed.length_square() < 1e-30 {
+            return h.normal;
+        }
+        let perturbed = perturbed.unit_vector();
+        let perturbed = if perturbed.dot(n) < 0.0 {
+            -perturbed
+        } else {
+            perturbed
+        };
+        face_against(perturbed, r, h)
+    }
 }
@@ -14,40 +14,48 @@ pub use texture::Texture;
 pub use world::World;
 
 use crate::object::aabb::AABB;
-use crate::utils::{Ray, Vec3};
+use crate::utils::{Color, Ray, Vec3};
 
 pub mod aabb;
 pub mod bvh;
 
 pub mod material;
+pub mod phase;
 pub mod texture;
 
 pub mod cube;
+pub mod cylinder;
+pub mod heightfield;
+pub mod plane;
+pub mod quad;
 pub mod rect;
 pub mod sphere;
+pub mod torus;
+pub mod triangle;
 
+pub mod alpha;
+pub mod animated;
+pub mod csg;
+pub mod instance;
 pub mod rotate;
+pub mod sdf;
 
 pub mod constant_medium;
 pub mod container;
+pub mod heterogeneous_medium;
 pub mod world;
 
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, Default)]
 pub enum Face {
+    #[default]
     Inward,
     Outward,
 }
 
-impl Default for Face {
-    fn default() -> Self {
-        Face::Inward
-    }
-}
-
 impl Face {
     /// Calculate facing by direction of the light beam and normal vector.
-    pub fn calc(p: &Vec3<f64>, r: &Ray) -> Face {
-        if p.dot(r.direction()) < 0.0 {
+    pub fn calc(normal: &Vec3<f64>, r: &Ray) -> Face {
+        if normal.dot(r.direction()) < 0.0 {
             Face::Inward
         } else {
             Face::Outward
@@ -59,6 +67,17 @@ impl Face {
 ///
 /// This structure contains the facing information(inward or outward), time of hit since the light
 /// beam was shoot, hit point, normal vector & material at that point.
+///
+/// `dpdu` & `dpdv` are the derivatives of the hit point along the texture coordinates, which
+/// orient normal & bump maps. Surfaces without meaningful texture coordinates leave them zero.
+///
+/// `footprint` is the world-space width of the pixel seen through the hit, for filtering
+/// textures over it. Primitives leave it as 0, which samples textures at a single point, and the
+/// renderer fills it in from the ray cone of the camera.
+///
+/// `object_id` identifies the top-level object in the world that was hit. Primitives leave it
+/// as 0, and `World` fills it in for every object added to it.
+#[derive(Clone)]
 pub struct HitRecord {
     pub f: Face,
     pub t: f64,
@@ -66,15 +85,38 @@ pub struct HitRecord {
     pub u: f64,
     pub v: f64,
     pub normal: Vec3<f64>,
+    pub dpdu: Vec3<f64>,
+    pub dpdv: Vec3<f64>,
     pub mat: Arc<dyn Material + Send + Sync>,
+    pub footprint: f64,
+    pub object_id: usize,
+}
+
+impl HitRecord {
+    /// Get the color of `texture` at the hit, averaged over the footprint of the hit if any.
+    pub fn texture_color(&self, texture: &TextureObject) -> Color {
+        if self.footprint <= 0.0 {
+            return texture.get_color(self.u, self.v, self.p);
+        }
+        // footprint in texture coordinates along each direction
+        let extent = |d: Vec3<f64>| {
+            let length = d.length();
+            if length > 0.0 {
+                self.footprint / length
+            } else {
+                0.0
+            }
+        };
+        texture.get_filtered_color(self.u, self.v, self.p, extent(self.dpdu), extent(self.dpdv))
+    }
 }
 
 impl Display for HitRecord {
     fn fmt(&self, f: &mut Formatter<'_>) -> Result {
         write!(
             f,
-            "{:?} t={} p={} normal={}",
-            self.f, self.t, self.p, self.normal
+            "{:?} t={} p={} normal={} object_id={}",
+            self.f, self.t, self.p, self.normal, self.object_id
         )
     }
 }
@@ -86,6 +128,18 @@ pub trait Hittable {
     /// like an infinite plane.
     fn bounding_box(&self) -> Option<&AABB>;
     fn hit(&self, r: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord>;
+
+    /// Get the pdf in solid angle of sampling `direction` from `origin` by `random`, for objects
+    /// used as area lights. Objects which can't be sampled return 0.
+    fn pdf_value(&self, _origin: Vec3<f64>, _direction: Vec3<f64>) -> f64 {
+        0.0
+    }
+
+    /// Get the direction from `origin` to a random point on the object, or `None` for objects
+    /// which can't be sampled.
+    fn random(&self, _origin: Vec3<f64>) -> Option<Vec3<f64>> {
+        None
+    }
 }
 
 /// Thread-safe, read-only objects that implement `Hittable` trait
@@ -95,8 +149,8 @@ pub type MaterialObject = Arc<dyn Material + Send + Sync>;
 /// Thread-safe, read-only objects that implement `Texture` trait
 pub type TextureObject = Arc<dyn Texture + Send + Sync>;
 
-/// These three function exists because explicit type conversion is needed
-/// to make these objects.
+// These three function exists because explicit type conversion is needed
+// to make these objects.
 
 pub fn make_hittable(h: impl Hittable + Send + Sync + 'static) -> HittableObject {
     Arc::new(h)
@@ -111,7 +165,7 @@ pub fn make_texture(t: impl Texture + Send + Sync + 'static) -> TextureObject {
 }
 
 pub fn make_sphere(center: Vec3<f64>, radius: f64, mat: &MaterialObject) -> HittableObject {
-    make_hittable(Sphere::new(center, radius, &mat))
+    make_hittable(Sphere::new(center, radius, mat))
 }
 
 pub fn make_bouncing_sphere(
@@ -124,5 +178,5 @@ pub fn make_bouncing_sphere(
 ) -> HittableObject {
     let mut c1 = center;
     c1.x += height;
-    make_hittable(MovingSphere::new(center, c1, t0, t1, radius, &mat))
+    make_hittable(MovingSphere::new(center, c1, t0, t1, radius, mat))
 }
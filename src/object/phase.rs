@@ -0,0 +1,179 @@
+//! Phase functions describing how participating media scatter light.
+//!
+//! Directions follow the ray: `wo` is the unit direction the light travels in before scattering
+//! and `wi` the one after, so a positive asymmetry means forward scattering. Every phase function
+//! is sampled exactly by its own distribution, which makes `eval` the pdf of `sample` as well.
+
+use std::f64::consts::PI;
+use std::sync::Arc;
+
+use rand::{thread_rng, Rng};
+
+use crate::object::material::FilteredRay;
+use crate::object::{HitRecord, Material, TextureObject};
+use crate::utils::{Color, Ray, Vec3};
+
+pub trait PhaseFunction {
+    /// Value of the phase function, which is also the pdf of `sample` in solid angle.
+    fn eval(&self, wo: Vec3<f64>, wi: Vec3<f64>) -> f64;
+    /// Sample a scattered direction for light travelling in direction `wo`.
+    fn sample(&self, wo: Vec3<f64>) -> Vec3<f64>;
+}
+
+/// Thread-safe, read-only objects that implement `PhaseFunction` trait
+pub type PhaseFunctionObject = Arc<dyn PhaseFunction + Send + Sync>;
+
+/// Build the direction with angle `acos(cos_theta)` to `w` & azimuth `phi` around it.
+fn spherical_direction(w: Vec3<f64>, cos_theta: f64, phi: f64) -> Vec3<f64> {
+    let a = if w.x.abs() > 0.9 {
+        Vec3::new(0.0, 1.0, 0.0)
+    } else {
+        Vec3::new(1.0, 0.0, 0.0)
+    };
+    let v = w.cross(a).unit_vector();
+    let u = w.cross(v);
+    let sin_theta = (1.0 - cos_theta * cos_theta).max(0.0).sqrt();
+    u * (sin_theta * phi.cos()) + v * (sin_theta * phi.sin()) + w * cos_theta
+}
+
+pub struct IsotropicPhase {}
+
+impl PhaseFunction for IsotropicPhase {
+    fn eval(&self, _wo: Vec3<f64>, _wi: Vec3<f64>) -> f64 {
+        1.0 / (4.0 * PI)
+    }
+
+    fn sample(&self, wo: Vec3<f64>) -> Vec3<f64> {
+        let mut rng = thread_rng();
+        spherical_direction(wo, rng.gen_range(-1.0, 1.0), rng.gen_range(0.0, 2.0 * PI))
+    }
+}
+
+/// Henyey-Greenstein phase function.
+///
+/// * `g`: Asymmetry in (-1, 1). Positive for forward scattering, negative for backward
+///   scattering & zero for isotropic scattering.
+pub struct HenyeyGreenstein {
+    pub g: f64,
+}
+
+impl HenyeyGreenstein {
+    pub fn new(g: f64) -> Self {
+        assert!(g > -1.0 && g < 1.0, "Asymmetry should be in (-1, 1).");
+        HenyeyGreenstein { g }
+    }
+
+    #[inline(always)]
+    fn eval_cos(&self, cos_theta: f64) -> f64 {
+        let g = self.g;
+        let denom = 1.0 + g * g - 2.0 * g * cos_theta;
+        (1.0 - g * g) / (4.0 * PI * denom * denom.sqrt())
+    }
+
+    fn sample_cos(&self, xi: f64) -> f64 {
+        let g = self.g;
+        if g.abs() < 1e-3 {
+            1.0 - 2.0 * xi
+        } else {
+            let s = (1.0 - g * g) / (1.0 - g + 2.0 * g * xi);
+            ((1.0 + g * g - s * s) / (2.0 * g)).clamp(-1.0, 1.0)
+        }
+    }
+}
+
+impl PhaseFunction for HenyeyGreenstein {
+    fn eval(&self, wo: Vec3<f64>, wi: Vec3<f64>) -> f64 {
+        self.eval_cos(wo.dot(wi))
+    }
+
+    fn sample(&self, wo: Vec3<f64>) -> Vec3<f64> {
+        let mut rng = thread_rng();
+        let cos_theta = self.sample_cos(rng.gen());
+        spherical_direction(wo, cos_theta, rng.gen_range(0.0, 2.0 * PI))
+    }
+}
+
+/// Mixture of two Henyey-Greenstein lobes, typically a forward & a backward one as in clouds.
+///
+/// * `weight`: Weight of the first lobe in [0, 1], the second one gets the rest.
+pub struct DoubleHenyeyGreenstein {
+    pub first: HenyeyGreenstein,
+    pub second: HenyeyGreenstein,
+    pub weight: f64,
+}
+
+impl DoubleHenyeyGreenstein {
+    pub fn new(g1: f64, g2: f64, weight: f64) -> Self {
+        assert!((0.0..=1.0).contains(&weight), "Weight should be in [0, 1].");
+        DoubleHenyeyGreenstein {
+            first: HenyeyGreenstein::new(g1),
+            second: HenyeyGreenstein::new(g2),
+            weight,
+        }
+    }
+}
+
+impl PhaseFunction for DoubleHenyeyGreenstein {
+    fn eval(&self, wo: Vec3<f64>, wi: Vec3<f64>) -> f64 {
+        self.weight * self.first.eval(wo, wi) + (1.0 - self.weight) * self.second.eval(wo, wi)
+    }
+
+    fn sample(&self, wo: Vec3<f64>) -> Vec3<f64> {
+        if thread_rng().gen::<f64>() < self.weight {
+            self.first.sample(wo)
+        } else {
+            self.second.sample(wo)
+        }
+    }
+}
+
+/// Rayleigh scattering by particles much smaller than the wavelength, like air molecules.
+pub struct Rayleigh {}
+
+impl PhaseFunction for Rayleigh {
+    fn eval(&self, wo: Vec3<f64>, wi: Vec3<f64>) -> f64 {
+        let cos_theta = wo.dot(wi);
+        3.0 / (16.0 * PI) * (1.0 + cos_theta * cos_theta)
+    }
+
+    fn sample(&self, wo: Vec3<f64>) -> Vec3<f64> {
+        let mut rng = thread_rng();
+        // invert the cdf (mu^3 + 3mu + 4) / 8 by Cardano's formula
+        let z = 8.0 * rng.gen::<f64>() - 4.0;
+        let d = (z * z / 4.0 + 1.0).sqrt();
+        let cos_theta = ((z / 2.0 + d).cbrt() + (z / 2.0 - d).cbrt()).clamp(-1.0, 1.0);
+        spherical_direction(wo, cos_theta, rng.gen_range(0.0, 2.0 * PI))
+    }
+}
+
+/// Material scattering light inside a medium by a phase function.
+pub struct PhaseMaterial {
+    pub albedo: TextureObject,
+    pub phase: PhaseFunctionObject,
+}
+
+impl Material for PhaseMaterial {
+    fn get_type(&self) -> &'static str {
+        "PhaseMaterial"
+    }
+
+    fn albedo(&self, h: &HitRecord) -> Color {
+        h.texture_color(&self.albedo)
+    }
+
+    fn scatter(&self, r: &Ray, h: &HitRecord) -> Option<FilteredRay> {
+        Some(FilteredRay {
+            attenuation: h.texture_color(&self.albedo),
+            scattered: Ray {
+                orig: h.p,
+                dir: self.phase.sample(r.dir.unit_vector()),
+                t: r.t,
+            },
+        })
+    }
+
+    fn scattering_pdf(&self, r: &Ray, _h: &HitRecord, scattered: &Ray) -> f64 {
+        self.phase
+            .eval(r.dir.unit_vector(), scattered.dir.unit_vector())
+    }
+}
@@ -0,0 +1,161 @@
+//! Flat primitives with arbitrary orientation: the infinite plane & the disk.
+
+use std::f64::consts::PI;
+
+use crate::object::aabb::AABB;
+use crate::object::cylinder::disk_extent;
+use crate::object::{Face, HitRecord, Hittable, MaterialObject};
+use crate::utils::transform::Transform;
+use crate::utils::{Ray, Vec3};
+
+/// Get two unit vectors perpendicular to the unit vector `normal` & to each other.
+fn tangent_frame(normal: Vec3<f64>) -> (Vec3<f64>, Vec3<f64>) {
+    let frame = Transform::from_axis(Vec3::zero(), normal);
+    (
+        frame.vector(Vec3::new(1.0, 0.0, 0.0)),
+        frame.vector(Vec3::new(0.0, 0.0, 1.0)),
+    )
+}
+
+/// Intersect a ray with the plane through `point` with unit normal `normal`.
+#[inline(always)]
+fn hit_plane(point: Vec3<f64>, normal: Vec3<f64>, r: &Ray, t_min: f64, t_max: f64) -> Option<f64> {
+    let denom = normal.dot(r.direction());
+    if denom.abs() < 1e-12 {
+        return None;
+    }
+    let t = (point - r.origin()).dot(normal) / denom;
+    if t < t_min || t > t_max {
+        None
+    } else {
+        Some(t)
+    }
+}
+
+/// An infinite plane through `point`, facing `normal`.
+///
+/// It has no bounding box, so containers test it outside of their BVH. The texture coordinates
+/// repeat every world unit along the plane.
+pub struct Plane {
+    pub point: Vec3<f64>,
+    pub normal: Vec3<f64>,
+    pub mat: MaterialObject,
+    tangent: Vec3<f64>,
+    bitangent: Vec3<f64>,
+}
+
+impl Plane {
+    pub fn new(point: Vec3<f64>, normal: Vec3<f64>, mat: &MaterialObject) -> Self {
+        let normal = normal.unit_vector();
+        let (tangent, bitangent) = tangent_frame(normal);
+        Plane {
+            point,
+            normal,
+            mat: mat.clone(),
+            tangent,
+            bitangent,
+        }
+    }
+}
+
+impl Hittable for Plane {
+    fn bounding_box(&self) -> Option<&AABB> {
+        None
+    }
+
+    fn hit(&self, r: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+        let t = hit_plane(self.point, self.normal, r, t_min, t_max)?;
+        let p = r.at(t);
+        let d = p - self.point;
+        let mut normal = self.normal;
+        let f = Face::calc(&normal, r);
+        if let Face::Outward = f {
+            normal = -normal;
+        }
+        Some(HitRecord {
+            f,
+            t,
+            p,
+            u: d.dot(self.tangent).rem_euclid(1.0),
+            v: d.dot(self.bitangent).rem_euclid(1.0),
+            normal,
+            dpdu: self.tangent,
+            dpdv: self.bitangent,
+            mat: self.mat.clone(),
+            footprint: 0.0,
+            object_id: 0,
+        })
+    }
+}
+
+/// A disk at `center` facing `normal`.
+///
+/// `u` is the angle around the center in turns & `v` the distance from the center relative to
+/// `radius`.
+pub struct Disk {
+    pub center: Vec3<f64>,
+    pub normal: Vec3<f64>,
+    pub radius: f64,
+    pub mat: MaterialObject,
+    tangent: Vec3<f64>,
+    bitangent: Vec3<f64>,
+    bounding_box: AABB,
+}
+
+impl Disk {
+    pub fn new(center: Vec3<f64>, normal: Vec3<f64>, radius: f64, mat: &MaterialObject) -> Self {
+        let normal = normal.unit_vector();
+        let (tangent, bitangent) = tangent_frame(normal);
+        // extent of the disk along each axis, padded so it never gets flat
+        let extent = disk_extent(normal, radius) + 0.001;
+        Disk {
+            center,
+            normal,
+            radius,
+            mat: mat.clone(),
+            tangent,
+            bitangent,
+            bounding_box: AABB {
+                min: center - extent,
+                max: center + extent,
+            },
+        }
+    }
+}
+
+impl Hittable for Disk {
+    fn bounding_box(&self) -> Option<&AABB> {
+        Some(&self.bounding_box)
+    }
+
+    fn hit(&self, r: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+        let t = hit_plane(self.center, self.normal, r, t_min, t_max)?;
+        let p = r.at(t);
+        let d = p - self.center;
+        let dist_square = d.length_square();
+        if dist_square > self.radius * self.radius {
+            return None;
+        }
+        let phi = d.dot(self.bitangent).atan2(d.dot(self.tangent));
+        let mut normal = self.normal;
+        let f = Face::calc(&normal, r);
+        if let Face::Outward = f {
+            normal = -normal;
+        }
+        Some(HitRecord {
+            f,
+            t,
+            p,
+            u: phi.rem_euclid(2.0 * PI) / (2.0 * PI),
+            v: dist_square.sqrt() / self.radius,
+            normal,
+            // u goes around the center & v outwards
+            dpdu: (self.bitangent * d.dot(self.tangent) - self.tangent * d.dot(self.bitangent))
+                * (2.0 * PI),
+            dpdv: d / dist_square.sqrt().max(1e-12) * self.radius,
+            mat: self.mat.clone(),
+            footprint: 0.0,
+            object_id: 0,
+        })
+    }
+}
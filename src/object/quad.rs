@@ -0,0 +1,127 @@
+//! Quad: a parallelogram spanned by two edge vectors, e.g. for tilted panels & area lights.
+
+use rand::{thread_rng, Rng};
+
+use crate::object::aabb::AABB;
+use crate::object::cylinder::bounding_points;
+use crate::object::{Face, HitRecord, Hittable, MaterialObject};
+use crate::utils::{Ray, Vec3};
+
+/// Thickness of the bounding box of flat objects lying in an axis plane.
+const PADDING: f64 = 0.0001;
+
+/// Bounding box of a flat object, padded so it never gets flat itself.
+pub(crate) fn flat_bounding_box(points: &[Vec3<f64>]) -> AABB {
+    let b = bounding_points(points);
+    AABB {
+        min: b.min - PADDING,
+        max: b.max + PADDING,
+    }
+}
+
+/// Pdf in solid angle of reaching a point of a flat object with `area` & unit `normal`, `t`
+/// along `direction` from its origin.
+#[inline(always)]
+pub(crate) fn solid_angle_pdf(direction: Vec3<f64>, t: f64, normal: Vec3<f64>, area: f64) -> f64 {
+    let length_square = direction.length_square();
+    let cosine = (direction.dot(normal) / length_square.sqrt()).abs();
+    if cosine == 0.0 {
+        return 0.0;
+    }
+    t * t * length_square / (cosine * area)
+}
+
+/// A parallelogram with a corner at `origin` & edges `u` & `v`.
+///
+/// It faces along `u` x `v`, with texture coordinates (0, 0) at `origin` & (1, 1) at the
+/// opposite corner.
+pub struct Quad {
+    pub origin: Vec3<f64>,
+    pub u: Vec3<f64>,
+    pub v: Vec3<f64>,
+    pub mat: MaterialObject,
+    normal: Vec3<f64>,
+    /// `u` x `v` / |`u` x `v`|^2, for planar coordinates of hit points.
+    w: Vec3<f64>,
+    area: f64,
+    bounding_box: AABB,
+}
+
+impl Quad {
+    pub fn new(origin: Vec3<f64>, u: Vec3<f64>, v: Vec3<f64>, mat: &MaterialObject) -> Self {
+        let n = u.cross(v);
+        Quad {
+            origin,
+            u,
+            v,
+            mat: mat.clone(),
+            normal: n.unit_vector(),
+            w: n / n.length_square(),
+            area: n.length(),
+            bounding_box: flat_bounding_box(&[origin, origin + u, origin + v, origin + u + v]),
+        }
+    }
+
+    pub fn area(&self) -> f64 {
+        self.area
+    }
+}
+
+impl Hittable for Quad {
+    fn bounding_box(&self) -> Option<&AABB> {
+        Some(&self.bounding_box)
+    }
+
+    fn hit(&self, r: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+        let denom = self.normal.dot(r.direction());
+        if denom.abs() < 1e-12 {
+            return None;
+        }
+        let t = (self.origin - r.origin()).dot(self.normal) / denom;
+        if t < t_min || t > t_max {
+            return None;
+        }
+        let p = r.at(t);
+        let d = p - self.origin;
+        let alpha = self.w.dot(d.cross(self.v));
+        let beta = self.w.dot(self.u.cross(d));
+        if !(0.0..=1.0).contains(&alpha) || !(0.0..=1.0).contains(&beta) {
+            return None;
+        }
+        let mut normal = self.normal;
+        let f = Face::calc(&normal, r);
+        if let Face::Outward = f {
+            normal = -normal;
+        }
+        Some(HitRecord {
+            f,
+            t,
+            p,
+            u: alpha,
+            v: beta,
+            normal,
+            dpdu: self.u,
+            dpdv: self.v,
+            mat: self.mat.clone(),
+            footprint: 0.0,
+            object_id: 0,
+        })
+    }
+
+    fn pdf_value(&self, origin: Vec3<f64>, direction: Vec3<f64>) -> f64 {
+        let r = Ray {
+            orig: origin,
+            dir: direction,
+            t: 0.0,
+        };
+        match self.hit(&r, 0.001, f64::INFINITY) {
+            Some(h) => solid_angle_pdf(direction, h.t, self.normal, self.area),
+            None => 0.0,
+        }
+    }
+
+    fn random(&self, origin: Vec3<f64>) -> Option<Vec3<f64>> {
+        let mut rng = thread_rng();
+        Some(self.origin + self.u * rng.gen::<f64>() + self.v * rng.gen::<f64>() - origin)
+    }
+}
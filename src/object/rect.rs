@@ -76,7 +76,11 @@ impl Hittable for XYRect {
             u: (x - self.x0) / (self.x1 - self.x0),
             v: (y - self.y0) / (self.y1 - self.y0),
             normal,
+            dpdu: Vec3::new(self.x1 - self.x0, 0.0, 0.0),
+            dpdv: Vec3::new(0.0, self.y1 - self.y0, 0.0),
             mat: self.mat.clone(),
+            footprint: 0.0,
+            object_id: 0,
         })
     }
 }
@@ -125,7 +129,11 @@ impl Hittable for XZRect {
             u: (x - self.x0) / (self.x1 - self.x0),
             v: (z - self.z0) / (self.z1 - self.z0),
             normal,
+            dpdu: Vec3::new(self.x1 - self.x0, 0.0, 0.0),
+            dpdv: Vec3::new(0.0, 0.0, self.z1 - self.z0),
             mat: self.mat.clone(),
+            footprint: 0.0,
+            object_id: 0,
         })
     }
 }
@@ -174,7 +182,11 @@ impl Hittable for YZRect {
             u: (y - self.y0) / (self.y1 - self.y0),
             v: (z - self.z0) / (self.z1 - self.z0),
             normal,
+            dpdu: Vec3::new(0.0, self.y1 - self.y0, 0.0),
+            dpdv: Vec3::new(0.0, 0.0, self.z1 - self.z0),
             mat: self.mat.clone(),
+            footprint: 0.0,
+            object_id: 0,
         })
     }
 }
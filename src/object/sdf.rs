@@ -0,0 +1,241 @@
+//! SDF: objects described by signed distance functions, intersected by sphere tracing.
+//!
+//! A distance function gives a lower bound of the distance from a point to the surface, negative
+//! inside the object. Marching along the ray by that distance never skips the surface.
+
+use std::f64::consts::PI;
+use std::sync::Arc;
+
+use crate::object::aabb::AABB;
+use crate::object::{Face, HitRecord, Hittable, MaterialObject};
+use crate::utils::{Ray, Vec3};
+
+/// Maximum number of steps along a ray.
+const MAX_STEPS: usize = 512;
+/// Distance to the surface that counts as a hit.
+const HIT_DISTANCE: f64 = 1e-5;
+/// Step for the finite differences of the normal.
+const NORMAL_DELTA: f64 = 1e-5;
+
+pub trait DistanceFunction {
+    fn distance(&self, p: Vec3<f64>) -> f64;
+}
+
+/// Thread-safe, read-only objects that implement `DistanceFunction` trait
+pub type DistanceFunctionObject = Arc<dyn DistanceFunction + Send + Sync>;
+
+pub fn make_distance_function(
+    d: impl DistanceFunction + Send + Sync + 'static,
+) -> DistanceFunctionObject {
+    Arc::new(d)
+}
+
+/// Any closure from points to distances is a distance function.
+impl<F: Fn(Vec3<f64>) -> f64> DistanceFunction for F {
+    fn distance(&self, p: Vec3<f64>) -> f64 {
+        self(p)
+    }
+}
+
+pub struct SdfSphere {
+    pub center: Vec3<f64>,
+    pub radius: f64,
+}
+
+impl DistanceFunction for SdfSphere {
+    fn distance(&self, p: Vec3<f64>) -> f64 {
+        (p - self.center).length() - self.radius
+    }
+}
+
+/// Axis-aligned box, spanning `half_size` to each side of `center`.
+pub struct SdfBox {
+    pub center: Vec3<f64>,
+    pub half_size: Vec3<f64>,
+}
+
+impl DistanceFunction for SdfBox {
+    fn distance(&self, p: Vec3<f64>) -> f64 {
+        let d = p - self.center;
+        let q = Vec3::new(
+            d.x.abs() - self.half_size.x,
+            d.y.abs() - self.half_size.y,
+            d.z.abs() - self.half_size.z,
+        );
+        let outside = Vec3::new(q.x.max(0.0), q.y.max(0.0), q.z.max(0.0)).length();
+        let inside = q.x.max(q.y).max(q.z).min(0.0);
+        outside + inside
+    }
+}
+
+/// Torus around the y axis through `center`.
+pub struct SdfTorus {
+    pub center: Vec3<f64>,
+    pub major_radius: f64,
+    pub minor_radius: f64,
+}
+
+impl DistanceFunction for SdfTorus {
+    fn distance(&self, p: Vec3<f64>) -> f64 {
+        let d = p - self.center;
+        let ring = (d.x * d.x + d.z * d.z).sqrt() - self.major_radius;
+        (ring * ring + d.y * d.y).sqrt() - self.minor_radius
+    }
+}
+
+/// Union of two shapes, blended over a distance of about `k`.
+pub struct SmoothUnion {
+    pub a: DistanceFunctionObject,
+    pub b: DistanceFunctionObject,
+    pub k: f64,
+}
+
+impl DistanceFunction for SmoothUnion {
+    fn distance(&self, p: Vec3<f64>) -> f64 {
+        let (da, db) = (self.a.distance(p), self.b.distance(p));
+        let h = (0.5 + 0.5 * (db - da) / self.k).clamp(0.0, 1.0);
+        db + (da - db) * h - self.k * h * (1.0 - h)
+    }
+}
+
+/// Infinite copies of a shape, one in every cell of size `period` around the origin.
+///
+/// A period of 0 along an axis disables the repetition along it. The shape should fit in the
+/// cell at the origin.
+pub struct Repetition {
+    pub inner: DistanceFunctionObject,
+    pub period: Vec3<f64>,
+}
+
+impl DistanceFunction for Repetition {
+    fn distance(&self, p: Vec3<f64>) -> f64 {
+        let wrap = |x: f64, period: f64| {
+            if period > 0.0 {
+                x - period * (x / period).round()
+            } else {
+                x
+            }
+        };
+        self.inner.distance(Vec3::new(
+            wrap(p.x, self.period.x),
+            wrap(p.y, self.period.y),
+            wrap(p.z, self.period.z),
+        ))
+    }
+}
+
+/// A shape twisted around the y axis by `rate` in degrees per unit of height.
+///
+/// Twisting stretches distances, so the result overestimates them: use
+/// `SdfObject::set_step_scale` to march more carefully.
+pub struct Twist {
+    pub inner: DistanceFunctionObject,
+    pub rate: f64,
+}
+
+impl DistanceFunction for Twist {
+    fn distance(&self, p: Vec3<f64>) -> f64 {
+        let (s, c) = (p.y * self.rate * PI / 180.0).sin_cos();
+        self.inner
+            .distance(Vec3::new(c * p.x - s * p.z, p.y, s * p.x + c * p.z))
+    }
+}
+
+/// An object bounded by `bounding_box`, whose surface is where the distance function is 0.
+///
+/// The texture coordinates are all 0, so it should be used with textures depending on the hit
+/// point only.
+pub struct SdfObject {
+    pub sdf: DistanceFunctionObject,
+    pub mat: MaterialObject,
+    step_scale: f64,
+    bounding_box: AABB,
+}
+
+impl SdfObject {
+    pub fn new(sdf: &DistanceFunctionObject, bounding_box: AABB, mat: &MaterialObject) -> Self {
+        SdfObject {
+            sdf: sdf.clone(),
+            mat: mat.clone(),
+            step_scale: 1.0,
+            bounding_box,
+        }
+    }
+
+    /// Scale every step by `scale` in (0, 1], for distance functions that overestimate.
+    pub fn set_step_scale(&mut self, scale: f64) {
+        assert!(
+            scale > 0.0 && scale <= 1.0,
+            "Step scale should be in (0, 1]."
+        );
+        self.step_scale = scale;
+    }
+
+    /// Estimate the outward normal by central differences of the distance function.
+    fn normal(&self, p: Vec3<f64>) -> Vec3<f64> {
+        let h = NORMAL_DELTA;
+        let dx = Vec3::new(h, 0.0, 0.0);
+        let dy = Vec3::new(0.0, h, 0.0);
+        let dz = Vec3::new(0.0, 0.0, h);
+        Vec3::new(
+            self.sdf.distance(p + dx) - self.sdf.distance(p - dx),
+            self.sdf.distance(p + dy) - self.sdf.distance(p - dy),
+            self.sdf.distance(p + dz) - self.sdf.distance(p - dz),
+        )
+        .unit_vector()
+    }
+}
+
+impl Hittable for SdfObject {
+    fn bounding_box(&self) -> Option<&AABB> {
+        Some(&self.bounding_box)
+    }
+
+    fn hit(&self, r: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+        let (t0, t1) = self.bounding_box.clip(r, t_min, t_max)?;
+        let speed = r.direction().length();
+        let mut t = t0;
+        let mut steps = 0;
+        // leave the surface first when the march starts at the ray origin: rays scattered from a
+        // hit start on it, and their start point may have overshot to the wrong side. Rays
+        // entering the bounding box may start on surfaces touching it, which they hit right away.
+        while t0 == t_min && self.sdf.distance(r.at(t)).abs() < HIT_DISTANCE {
+            t += HIT_DISTANCE / speed;
+            steps += 1;
+            if t > t1 || steps >= MAX_STEPS {
+                return None;
+            }
+        }
+        // march on the side of the surface where the ray is, so it can leave the object too
+        let side = self.sdf.distance(r.at(t)).signum();
+        for _ in steps..MAX_STEPS {
+            let d = self.sdf.distance(r.at(t)) * side;
+            if d < HIT_DISTANCE {
+                let p = r.at(t);
+                let mut normal = self.normal(p);
+                let f = Face::calc(&normal, r);
+                if let Face::Outward = f {
+                    normal = -normal;
+                }
+                return Some(HitRecord {
+                    f,
+                    t,
+                    p,
+                    u: 0.0,
+                    v: 0.0,
+                    normal,
+                    dpdu: Vec3::zero(),
+                    dpdv: Vec3::zero(),
+                    mat: self.mat.clone(),
+                    footprint: 0.0,
+                    object_id: 0,
+                });
+            }
+            t += d * self.step_scale / speed;
+            if t > t1 {
+                return None;
+            }
+        }
+        None
+    }
+}
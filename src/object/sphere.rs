@@ -35,6 +35,16 @@ impl Sphere {
     }
 }
 
+/// Derivatives of a point at `d` from the center along the texture coordinates of a sphere.
+#[inline(always)]
+fn sphere_partials(d: Vec3<f64>) -> (Vec3<f64>, Vec3<f64>) {
+    // u grows around y from +z towards +x, v from the south to the north pole
+    let rho = (d.x * d.x + d.z * d.z).sqrt().max(1e-12);
+    let dpdu = Vec3::new(d.z, 0.0, -d.x) * (2.0 * PI);
+    let dpdv = Vec3::new(-d.y * d.x / rho, rho, -d.y * d.z / rho) * PI;
+    (dpdu, dpdv)
+}
+
 /// calculate if a ray will hit a sphere
 /// $$t^2 \vec{\mathbf{b}}\cdot\vec{\mathbf{b}}
 ///     + 2t \vec{\mathbf{b}} \cdot \vec{(\mathbf{a}-\mathbf{c})}
@@ -53,7 +63,7 @@ impl Hittable for Sphere {
         let c = t1.length_square() - self.radius * self.radius;
 
         let discriminant = half_b * half_b - a * c;
-        return if discriminant > 0.0 {
+        if discriminant > 0.0 {
             let mut t = (-half_b - discriminant.sqrt()) / a;
             if t > t_max || t < t_min {
                 // another root
@@ -64,11 +74,12 @@ impl Hittable for Sphere {
             }
             let p = r.at(t);
             let mut normal = (p - self.center) / self.radius;
-            let f = Face::calc(&normal, &r);
+            let f = Face::calc(&normal, r);
             if let Face::Outward = f {
                 normal = -normal;
             }
             let (u, v) = self.get_sphere_uv(p - self.center);
+            let (dpdu, dpdv) = sphere_partials(p - self.center);
             Some(HitRecord {
                 f,
                 t,
@@ -76,11 +87,15 @@ impl Hittable for Sphere {
                 u,
                 v,
                 normal,
+                dpdu,
+                dpdv,
                 mat: Arc::clone(&self.mat),
+                footprint: 0.0,
+                object_id: 0,
             })
         } else {
             None
-        };
+        }
     }
 }
 
@@ -151,7 +166,7 @@ impl Hittable for MovingSphere {
         let c = t1.length_square() - self.radius * self.radius;
 
         let discriminant = half_b * half_b - a * c;
-        return if discriminant > 0.0 {
+        if discriminant > 0.0 {
             let mut t = (-half_b - discriminant.sqrt()) / a;
             if t > t_max || t < t_min {
                 // another root
@@ -162,8 +177,9 @@ impl Hittable for MovingSphere {
             }
             let p = r.at(t);
             let (u, v) = self.get_sphere_uv(p - center);
+            let (dpdu, dpdv) = sphere_partials(p - center);
             let mut normal = (p - c) / self.radius;
-            let f = Face::calc(&normal, &r);
+            let f = Face::calc(&normal, r);
             if let Face::Outward = f {
                 normal = -normal;
             }
@@ -174,10 +190,14 @@ impl Hittable for MovingSphere {
                 u,
                 v,
                 normal,
+                dpdu,
+                dpdv,
                 mat: Arc::clone(&self.mat),
+                footprint: 0.0,
+                object_id: 0,
             })
         } else {
             None
-        };
+        }
     }
 }
@@ -1,3 +1,4 @@
+use std::ops::{Add, Mul};
 use std::sync::Arc;
 
 use crate::utils::perlin::Perlin;
@@ -5,6 +6,15 @@ use crate::utils::{Color, Picture, Vec3};
 
 pub trait Texture {
     fn get_color(&self, u: f64, v: f64, p: Vec3<f64>) -> Color;
+    /// Opacity at the given point in [0, 1], used by alpha masks. Textures are opaque by default.
+    fn get_alpha(&self, _u: f64, _v: f64, _p: Vec3<f64>) -> f32 {
+        1.0
+    }
+    /// Color averaged over a footprint `du` wide along `u` & `dv` wide along `v`, to keep
+    /// textures seen from afar from aliasing. Textures are point sampled by default.
+    fn get_filtered_color(&self, u: f64, v: f64, p: Vec3<f64>, _du: f64, _dv: f64) -> Color {
+        self.get_color(u, v, p)
+    }
 }
 
 pub struct SolidColor {
@@ -94,17 +104,257 @@ impl Texture for MarbleTexture {
     }
 }
 
-// Spherical image texture
+/// Reconstruction of image textures between the texels.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum TextureFilter {
+    /// The texel under the point, blocky up close.
+    Nearest,
+    /// Linear blend of the 2x2 closest texels.
+    Bilinear,
+    /// Catmull-Rom spline through the 4x4 closest texels, sharper than bilinear.
+    Bicubic,
+}
+
+/// Continuation of image textures outside of [0, 1] texture coordinates.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum WrapMode {
+    /// Tile the image.
+    Repeat,
+    /// Extend the edge texels.
+    Clamp,
+    /// Tile the image, flipping every other tile.
+    Mirror,
+}
+
+impl WrapMode {
+    /// Bring texel index `i` inside a row or column of `n` texels.
+    fn wrap(self, i: i64, n: usize) -> usize {
+        let n = n as i64;
+        let i = match self {
+            WrapMode::Repeat => i.rem_euclid(n),
+            WrapMode::Clamp => i.clamp(0, n - 1),
+            WrapMode::Mirror => {
+                let i = i.rem_euclid(2 * n);
+                if i < n {
+                    i
+                } else {
+                    2 * n - 1 - i
+                }
+            }
+        };
+        i as usize
+    }
+}
+
+/// Catmull-Rom weights of 4 consecutive texels, for a point `t` past the second one.
+fn catmull_rom(t: f32) -> [f32; 4] {
+    let t2 = t * t;
+    let t3 = t2 * t;
+    [
+        0.5 * (-t3 + 2.0 * t2 - t),
+        0.5 * (3.0 * t3 - 5.0 * t2 + 2.0),
+        0.5 * (-3.0 * t3 + 4.0 * t2 + t),
+        0.5 * (t3 - t2),
+    ]
+}
+
+/// Halve `picture` along both axes by averaging blocks of 2x2 pixels. Odd sizes round up,
+/// repeating the last row or column.
+fn downsample(picture: &Picture) -> Picture {
+    let width = picture.width.div_ceil(2);
+    let height = picture.height.div_ceil(2);
+    let mut result = Picture::new(width, height);
+    let mut alpha = Vec::with_capacity(width * height);
+    for y in 0..height {
+        for x in 0..width {
+            let (x0, x1) = (2 * x, (2 * x + 1).min(picture.width - 1));
+            let (y0, y1) = (2 * y, (2 * y + 1).min(picture.height - 1));
+            let block = [(x0, y0), (x1, y0), (x0, y1), (x1, y1)];
+            result.data[y * width + x] = block
+                .iter()
+                .fold(Color::zero(), |sum, &(x, y)| sum + picture.at(x, y))
+                * 0.25;
+            alpha.push(
+                block
+                    .iter()
+                    .map(|&(x, y)| picture.alpha_at(x, y))
+                    .sum::<f32>()
+                    * 0.25,
+            );
+        }
+    }
+    if picture.alpha.is_some() {
+        result.alpha = Some(alpha);
+    }
+    result
+}
+
+/// Successive halvings of `picture` down to a single pixel.
+fn build_mipmaps(picture: &Picture) -> Vec<Picture> {
+    let mut mipmaps: Vec<Picture> = Vec::new();
+    loop {
+        let last = mipmaps.last().unwrap_or(picture);
+        if last.width <= 1 && last.height <= 1 {
+            return mipmaps;
+        }
+        let next = downsample(last);
+        mipmaps.push(next);
+    }
+}
+
+/// Texture from a picture, with `u` running across it from the left & `v` up from the bottom.
+///
+/// Texture coordinates go through the UV transform, are continued outside of the picture by the
+/// wrap mode & reconstructed between texels by the filter. Hits with a footprint blend the two
+/// mipmap levels whose texels are closest in size to the footprint.
 pub struct ImageTexture {
     pub image: Arc<Picture>,
+    filter: TextureFilter,
+    wrap: WrapMode,
+    scale: (f64, f64),
+    /// Sine & cosine of the rotation of texture coordinates.
+    rotation: (f64, f64),
+    offset: (f64, f64),
+    /// Halvings of `image` down to a single pixel, empty when mipmapping is off.
+    mipmaps: Vec<Picture>,
+}
+
+impl ImageTexture {
+    /// Create a bilinear, repeating & mipmapped texture of `image`.
+    pub fn new(image: Arc<Picture>) -> Self {
+        let mipmaps = build_mipmaps(&image);
+        ImageTexture {
+            image,
+            filter: TextureFilter::Bilinear,
+            wrap: WrapMode::Repeat,
+            scale: (1.0, 1.0),
+            rotation: (0.0, 1.0),
+            offset: (0.0, 0.0),
+            mipmaps,
+        }
+    }
+
+    pub fn set_filter(&mut self, filter: TextureFilter) {
+        self.filter = filter;
+    }
+
+    pub fn set_wrap(&mut self, wrap: WrapMode) {
+        self.wrap = wrap;
+    }
+
+    /// Scale texture coordinates by `scale`, then rotate them counterclockwise by `degrees`
+    /// around (0, 0) & shift them by `offset`.
+    pub fn set_uv_transform(&mut self, scale: (f64, f64), degrees: f64, offset: (f64, f64)) {
+        let angle = degrees.to_radians();
+        self.scale = scale;
+        self.rotation = (angle.sin(), angle.cos());
+        self.offset = offset;
+    }
+
+    /// Turn filtering over the footprint of hits on or off. Without it, distant textures alias.
+    pub fn set_mipmapping(&mut self, enabled: bool) {
+        if !enabled {
+            self.mipmaps.clear();
+        } else if self.mipmaps.is_empty() {
+            self.mipmaps = build_mipmaps(&self.image);
+        }
+    }
+
+    fn transform(&self, u: f64, v: f64) -> (f64, f64) {
+        let (u, v) = (u * self.scale.0, v * self.scale.1);
+        let (sin, cos) = self.rotation;
+        (
+            u * cos - v * sin + self.offset.0,
+            u * sin + v * cos + self.offset.1,
+        )
+    }
+
+    /// Get mipmap `level`, where level 0 is the image itself.
+    fn level(&self, level: usize) -> &Picture {
+        match level {
+            0 => &self.image,
+            _ => &self.mipmaps[level - 1],
+        }
+    }
+
+    /// Reconstruct the values read by `texel` from `picture` at texture coordinates (`u`, `v`).
+    fn sample<T, F>(&self, picture: &Picture, u: f64, v: f64, texel: F) -> T
+    where
+        T: Copy + Add<Output = T> + Mul<f32, Output = T>,
+        F: Fn(&Picture, usize, usize) -> T,
+    {
+        let fetch = |x: i64, y: i64| {
+            texel(
+                picture,
+                self.wrap.wrap(x, picture.width),
+                self.wrap.wrap(y, picture.height),
+            )
+        };
+        // position in texels from the top left corner of the picture
+        let x = u * picture.width as f64;
+        let y = (1.0 - v) * picture.height as f64;
+        if let TextureFilter::Nearest = self.filter {
+            return fetch(x.floor() as i64, y.floor() as i64);
+        }
+        // texel centers sit half a texel in
+        let (x, y) = (x - 0.5, y - 0.5);
+        let (x0, y0) = (x.floor(), y.floor());
+        let (tx, ty) = ((x - x0) as f32, (y - y0) as f32);
+        let (x0, y0) = (x0 as i64, y0 as i64);
+        match self.filter {
+            TextureFilter::Bicubic => {
+                let (wx, wy) = (catmull_rom(tx), catmull_rom(ty));
+                let row = |y: i64| {
+                    (1..4).fold(fetch(x0 - 1, y) * wx[0], |sum, i| {
+                        sum + fetch(x0 - 1 + i as i64, y) * wx[i]
+                    })
+                };
+                (1..4).fold(row(y0 - 1) * wy[0], |sum, j| {
+                    sum + row(y0 - 1 + j as i64) * wy[j]
+                })
+            }
+            _ => {
+                let top = fetch(x0, y0) * (1.0 - tx) + fetch(x0 + 1, y0) * tx;
+                let bottom = fetch(x0, y0 + 1) * (1.0 - tx) + fetch(x0 + 1, y0 + 1) * tx;
+                top * (1.0 - ty) + bottom * ty
+            }
+        }
+    }
+
+    fn sample_color(&self, level: usize, u: f64, v: f64) -> Color {
+        // the bicubic spline overshoots around sharp edges
+        self.sample(self.level(level), u, v, Picture::at)
+            .apply(|c| c.max(0.0))
+    }
 }
 
 impl Texture for ImageTexture {
     fn get_color(&self, u: f64, v: f64, _p: Vec3<f64>) -> Color {
-        // clamp u,v to image.width, image.height
-        // u, v should both in range [0, 1](mathematically)
-        let u = (u * self.image.width as f64) as usize;
-        let v = ((1.0 - v) * self.image.height as f64) as usize;
-        self.image.at(u, v)
+        let (u, v) = self.transform(u, v);
+        self.sample_color(0, u, v)
+    }
+
+    fn get_alpha(&self, u: f64, v: f64, _p: Vec3<f64>) -> f32 {
+        let (u, v) = self.transform(u, v);
+        self.sample(&self.image, u, v, Picture::alpha_at)
+            .clamp(0.0, 1.0)
+    }
+
+    fn get_filtered_color(&self, u: f64, v: f64, _p: Vec3<f64>, du: f64, dv: f64) -> Color {
+        // size of the footprint in texels of the image, after the scale of the UV transform
+        let stretch = self.scale.0.abs().max(self.scale.1.abs());
+        let texels = (du * self.image.width as f64).max(dv * self.image.height as f64) * stretch;
+        let (u, v) = self.transform(u, v);
+        if self.mipmaps.is_empty() || texels <= 1.0 {
+            return self.sample_color(0, u, v);
+        }
+        let lod = texels.log2().min(self.mipmaps.len() as f64);
+        let level = lod.floor() as usize;
+        let t = (lod - level as f64) as f32;
+        let fine = self.sample_color(level, u, v);
+        if t == 0.0 {
+            return fine;
+        }
+        fine * (1.0 - t) + self.sample_color(level + 1, u, v) * t
     }
 }
@@ -0,0 +1,100 @@
+//! Torus: a ring swept by a circle around an axis.
+
+use std::f64::consts::PI;
+
+use crate::object::aabb::AABB;
+use crate::object::cylinder::{disk_extent, to_record, turns};
+use crate::object::{HitRecord, Hittable, MaterialObject};
+use crate::utils::poly::solve_quartic;
+use crate::utils::transform::Transform;
+use crate::utils::{Ray, Vec3};
+
+/// A torus around `center`, whose tube of `minor_radius` circles `axis` at `major_radius`.
+///
+/// `u` goes around the axis & `v` around the tube, both in turns.
+pub struct Torus {
+    pub major_radius: f64,
+    pub minor_radius: f64,
+    pub mat: MaterialObject,
+    frame: Transform,
+    bounding_box: AABB,
+}
+
+impl Torus {
+    pub fn new(
+        center: Vec3<f64>,
+        axis: Vec3<f64>,
+        major_radius: f64,
+        minor_radius: f64,
+        mat: &MaterialObject,
+    ) -> Self {
+        let extent = disk_extent(axis.unit_vector(), major_radius) + minor_radius;
+        Torus {
+            major_radius,
+            minor_radius,
+            mat: mat.clone(),
+            frame: Transform::from_axis(center, axis),
+            bounding_box: AABB {
+                min: center - extent,
+                max: center + extent,
+            },
+        }
+    }
+}
+
+impl Hittable for Torus {
+    fn bounding_box(&self) -> Option<&AABB> {
+        Some(&self.bounding_box)
+    }
+
+    fn hit(&self, r: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+        let (big_r, small_r) = (self.major_radius, self.minor_radius);
+        let o = self.frame.inverse_point(r.origin());
+        let d = self.frame.inverse_vector(r.direction());
+        // keep the coefficients well-conditioned: use a unit direction & start the ray near the
+        // torus, without skipping past its bounding sphere
+        let len = d.length();
+        let d = d / len;
+        let shift = (o.length() - (big_r + small_r)).max(0.0);
+        let o = o + d * shift;
+
+        // solve (|p|^2 + R^2 - r^2)^2 = 4R^2(p_x^2 + p_z^2) for the ray, a quartic in t
+        let k = big_r * big_r - small_r * small_r;
+        let four_r2 = 4.0 * big_r * big_r;
+        let b = 2.0 * o.dot(d);
+        let c = o.length_square() + k;
+        let e = d.x * d.x + d.z * d.z;
+        let f = 2.0 * (o.x * d.x + o.z * d.z);
+        let g = o.x * o.x + o.z * o.z;
+        let roots = solve_quartic([
+            1.0,
+            2.0 * b,
+            b * b + 2.0 * c - four_r2 * e,
+            2.0 * b * c - four_r2 * f,
+            c * c - four_r2 * g,
+        ]);
+
+        for root in roots {
+            let t = (root + shift) / len;
+            if t < t_min || t > t_max {
+                continue;
+            }
+            let p = o + d * root;
+            let s = p.length_square() + k;
+            let normal = Vec3::new(
+                p.x * (s - 2.0 * big_r * big_r),
+                p.y * s,
+                p.z * (s - 2.0 * big_r * big_r),
+            )
+            .unit_vector();
+            let rho = (p.x * p.x + p.z * p.z).sqrt();
+            let ring = rho - big_r;
+            // v goes around the tube, upwards on its outer side
+            let rho = rho.max(1e-12);
+            let dpdv = Vec3::new(-p.y * p.x / rho, ring, -p.y * p.z / rho) * (2.0 * PI);
+            let hit = (t, normal, turns(p.x, p.z), turns(ring, p.y), dpdv);
+            return Some(to_record(&self.frame, r, hit, &self.mat));
+        }
+        None
+    }
+}
@@ -0,0 +1,191 @@
+//! Triangle: the building block of meshes, which can be sampled as an area light too.
+
+use rand::{thread_rng, Rng};
+
+use crate::object::aabb::AABB;
+use crate::object::quad::{flat_bounding_box, solid_angle_pdf};
+use crate::object::{Face, HitRecord, Hittable, MaterialObject};
+use crate::utils::{Ray, Vec3};
+
+/// Intersect a ray with triangle (`p0`, `p1`, `p2`) by the Möller-Trumbore algorithm, returning
+/// `t` & the barycentric coordinates of `p1` & `p2`.
+pub(crate) fn hit_triangle(
+    r: &Ray,
+    p0: Vec3<f64>,
+    p1: Vec3<f64>,
+    p2: Vec3<f64>,
+    t_min: f64,
+    t_max: f64,
+) -> Option<(f64, f64, f64)> {
+    let e1 = p1 - p0;
+    let e2 = p2 - p0;
+    let pv = r.direction().cross(e2);
+    let det = e1.dot(pv);
+    if det.abs() < 1e-12 {
+        return None;
+    }
+    let inv_det = 1.0 / det;
+    let tv = r.origin() - p0;
+    let b1 = tv.dot(pv) * inv_det;
+    if !(0.0..=1.0).contains(&b1) {
+        return None;
+    }
+    let qv = tv.cross(e1);
+    let b2 = r.direction().dot(qv) * inv_det;
+    if b2 < 0.0 || b1 + b2 > 1.0 {
+        return None;
+    }
+    let t = e2.dot(qv) * inv_det;
+    if t < t_min || t > t_max {
+        return None;
+    }
+    Some((t, b1, b2))
+}
+
+/// Put the interpolated shading normal `smooth` on the side of the `geometric` normal, or fall
+/// back to the geometric one if `smooth` is zero, returning a unit vector.
+///
+/// Vertex normals of meshes may point against their winding.
+pub(crate) fn smooth_normal(smooth: Vec3<f64>, geometric: Vec3<f64>) -> Vec3<f64> {
+    if smooth.length_square() < 1e-20 {
+        return geometric.unit_vector();
+    }
+    let smooth = smooth.unit_vector();
+    if smooth.dot(geometric) < 0.0 {
+        -smooth
+    } else {
+        smooth
+    }
+}
+
+/// A triangle with corners `vertices`, facing along (v1 - v0) x (v2 - v0).
+///
+/// Per-vertex normals give smooth shading & per-vertex texture coordinates are interpolated,
+/// otherwise `u` & `v` are the barycentric coordinates of the second & third vertex.
+pub struct Triangle {
+    pub vertices: [Vec3<f64>; 3],
+    pub mat: MaterialObject,
+    normals: Option<[Vec3<f64>; 3]>,
+    uvs: Option<[(f64, f64); 3]>,
+    normal: Vec3<f64>,
+    dpdu: Vec3<f64>,
+    dpdv: Vec3<f64>,
+    area: f64,
+    bounding_box: AABB,
+}
+
+impl Triangle {
+    pub fn new(vertices: [Vec3<f64>; 3], mat: &MaterialObject) -> Self {
+        let (e1, e2) = (vertices[1] - vertices[0], vertices[2] - vertices[0]);
+        let n = e1.cross(e2);
+        Triangle {
+            vertices,
+            mat: mat.clone(),
+            normals: None,
+            uvs: None,
+            normal: n.unit_vector(),
+            dpdu: e1,
+            dpdv: e2,
+            area: n.length() / 2.0,
+            bounding_box: flat_bounding_box(&vertices),
+        }
+    }
+
+    /// Set the normals at the vertices for smooth shading.
+    ///
+    /// Zero normals, like those averaged from faces that cancel out, fall back to the normal of
+    /// the triangle.
+    pub fn set_normals(&mut self, normals: [Vec3<f64>; 3]) {
+        let unit = |n: Vec3<f64>| {
+            if n.length_square() > 0.0 {
+                n.unit_vector()
+            } else {
+                self.normal
+            }
+        };
+        self.normals = Some([unit(normals[0]), unit(normals[1]), unit(normals[2])]);
+    }
+
+    pub fn set_uvs(&mut self, uvs: [(f64, f64); 3]) {
+        self.uvs = Some(uvs);
+        // solve for the derivatives along u & v from the edges, unless the uvs are degenerate
+        let [p0, p1, p2] = self.vertices;
+        let (e1, e2) = (p1 - p0, p2 - p0);
+        let (du1, dv1) = (uvs[1].0 - uvs[0].0, uvs[1].1 - uvs[0].1);
+        let (du2, dv2) = (uvs[2].0 - uvs[0].0, uvs[2].1 - uvs[0].1);
+        let det = du1 * dv2 - dv1 * du2;
+        if det.abs() > 1e-12 {
+            self.dpdu = (e1 * dv2 - e2 * dv1) / det;
+            self.dpdv = (e2 * du1 - e1 * du2) / det;
+        }
+    }
+
+    pub fn area(&self) -> f64 {
+        self.area
+    }
+}
+
+impl Hittable for Triangle {
+    fn bounding_box(&self) -> Option<&AABB> {
+        Some(&self.bounding_box)
+    }
+
+    fn hit(&self, r: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+        let [p0, p1, p2] = self.vertices;
+        let (t, b1, b2) = hit_triangle(r, p0, p1, p2, t_min, t_max)?;
+        let b0 = 1.0 - b1 - b2;
+        // the facing follows the actual surface, the shading normal is flipped to match it
+        let f = Face::calc(&self.normal, r);
+        let mut normal = match &self.normals {
+            Some([n0, n1, n2]) => smooth_normal(*n0 * b0 + *n1 * b1 + *n2 * b2, self.normal),
+            None => self.normal,
+        };
+        if let Face::Outward = f {
+            normal = -normal;
+        }
+        let (u, v) = match &self.uvs {
+            Some([uv0, uv1, uv2]) => (
+                uv0.0 * b0 + uv1.0 * b1 + uv2.0 * b2,
+                uv0.1 * b0 + uv1.1 * b1 + uv2.1 * b2,
+            ),
+            None => (b1, b2),
+        };
+        Some(HitRecord {
+            f,
+            t,
+            p: r.at(t),
+            u,
+            v,
+            normal,
+            dpdu: self.dpdu,
+            dpdv: self.dpdv,
+            mat: self.mat.clone(),
+            footprint: 0.0,
+            object_id: 0,
+        })
+    }
+
+    fn pdf_value(&self, origin: Vec3<f64>, direction: Vec3<f64>) -> f64 {
+        let r = Ray {
+            orig: origin,
+            dir: direction,
+            t: 0.0,
+        };
+        match self.hit(&r, 0.001, f64::INFINITY) {
+            Some(h) => solid_angle_pdf(direction, h.t, self.normal, self.area),
+            None => 0.0,
+        }
+    }
+
+    /// Sample a point uniformly by folding the unit square onto the triangle.
+    fn random(&self, origin: Vec3<f64>) -> Option<Vec3<f64>> {
+        let mut rng = thread_rng();
+        let (mut a, mut b) = (rng.gen::<f64>(), rng.gen::<f64>());
+        if a + b > 1.0 {
+            a = 1.0 - a;
+            b = 1.0 - b;
+        }
+        let [p0, p1, p2] = self.vertices;
+        Some(p0 + (p1 - p0) * a + (p2 - p0) * b - origin)
+    }
+}
@@ -1,10 +1,12 @@
 use std::sync::Arc;
 
+use rand::{thread_rng, Rng};
+
 use crate::object::aabb::AABB;
 use crate::object::container::Container;
 use crate::object::{HitRecord, Hittable, HittableObject};
 use crate::render::skybox::{ColorGradientSkyBox, SkyBox};
-use crate::utils::{Color, Ray};
+use crate::utils::{Color, Ray, Vec3};
 
 /// Object representing a world to render
 /// Every object in the hittable list is read-only
@@ -12,12 +14,43 @@ use crate::utils::{Color, Ray};
 pub struct World {
     sky_box: Arc<dyn SkyBox + Send + Sync>,
     container: Container,
+    object_count: usize,
+    lights: Vec<HittableObject>,
+}
+
+/// Wrapper that stamps the id of a top-level object onto every hit record it produces.
+struct TaggedObject {
+    id: usize,
+    hittable: HittableObject,
+}
+
+impl Hittable for TaggedObject {
+    #[inline(always)]
+    fn bounding_box(&self) -> Option<&AABB> {
+        self.hittable.bounding_box()
+    }
+
+    #[inline(always)]
+    fn hit(&self, r: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+        self.hittable.hit(r, t_min, t_max).map(|mut rec| {
+            rec.object_id = self.id;
+            rec
+        })
+    }
+}
+
+impl Default for World {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl World {
     pub fn new() -> World {
         World {
             container: Container::new(),
+            object_count: 0,
+            lights: vec![],
             sky_box: Arc::new(ColorGradientSkyBox {
                 v1: Color::new(1.0, 1.0, 1.0),
                 v2: Color::new(0.5, 0.7, 1.0),
@@ -25,8 +58,49 @@ impl World {
         }
     }
 
+    /// Add an object to the world. Objects are numbered from 1 in the order they are added,
+    /// and hits on them carry that number as `HitRecord::object_id`.
     pub fn add_hittable(&mut self, h: &HittableObject) {
-        self.container.add_hittable(h);
+        self.object_count += 1;
+        let tagged: HittableObject = Arc::new(TaggedObject {
+            id: self.object_count,
+            hittable: h.clone(),
+        });
+        self.container.add_hittable(&tagged);
+    }
+
+    /// Sample `light` directly when scattering off diffuse surfaces, to find small lights with
+    /// fewer samples. The light should support `Hittable::random`, and it still has to be added
+    /// with `add_hittable` to be seen.
+    pub fn add_light(&mut self, light: &HittableObject) {
+        self.lights.push(light.clone());
+    }
+
+    pub fn has_lights(&self) -> bool {
+        !self.lights.is_empty()
+    }
+
+    /// Get the pdf in solid angle of `sample_light` choosing `direction` from `origin`.
+    pub fn light_pdf(&self, origin: Vec3<f64>, direction: Vec3<f64>) -> f64 {
+        let sum: f64 = self
+            .lights
+            .iter()
+            .map(|l| l.pdf_value(origin, direction))
+            .sum();
+        sum / self.lights.len() as f64
+    }
+
+    /// Get the direction from `origin` to a random point of a light picked uniformly.
+    pub fn sample_light(&self, origin: Vec3<f64>) -> Option<Vec3<f64>> {
+        if self.lights.is_empty() {
+            return None;
+        }
+        let i = thread_rng().gen_range(0, self.lights.len());
+        self.lights[i].random(origin)
+    }
+
+    pub fn get_object_count(&self) -> usize {
+        self.object_count
     }
 
     pub fn update_metadata(&mut self) {
@@ -43,6 +117,8 @@ impl World {
 
     pub fn clear(&mut self) {
         self.container.clear();
+        self.object_count = 0;
+        self.lights.clear();
     }
 }
 
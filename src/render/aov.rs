@@ -0,0 +1,136 @@
+//! AOV: arbitrary output variables rendered alongside the beauty picture.
+//!
+//! Every AOV is evaluated at the first hit of the camera ray. Continuous variables (normal,
+//! albedo, depth & position) are averaged over all samples of a pixel, while categorical ones
+//! (material type & object id) are taken from a single sample since averaging them is meaningless.
+
+use crate::object::HitRecord;
+use crate::utils::{Color, Picture, Ray};
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Aov {
+    /// Shading normal, facing the camera.
+    Normal,
+    /// Base color from the texture of the material.
+    Albedo,
+    /// Distance from the camera along the ray, stored in all three channels.
+    Depth,
+    /// Hit point in world space.
+    Position,
+    /// Hash of `Material::get_type`, stored in all three channels.
+    Material,
+    /// `HitRecord::object_id`, stored in all three channels.
+    ObjectId,
+}
+
+impl Aov {
+    pub const ALL: [Aov; 6] = [
+        Aov::Normal,
+        Aov::Albedo,
+        Aov::Depth,
+        Aov::Position,
+        Aov::Material,
+        Aov::ObjectId,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Aov::Normal => "normal",
+            Aov::Albedo => "albedo",
+            Aov::Depth => "depth",
+            Aov::Position => "position",
+            Aov::Material => "material",
+            Aov::ObjectId => "object_id",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Aov> {
+        Aov::ALL.iter().find(|a| a.name() == name).copied()
+    }
+
+    /// Whether this AOV holds ids instead of values that can be averaged.
+    pub fn is_categorical(&self) -> bool {
+        matches!(self, Aov::Material | Aov::ObjectId)
+    }
+
+    /// Value of this AOV for a camera ray & its first hit. Misses are recorded as zero.
+    pub fn evaluate(&self, r: &Ray, h: Option<&HitRecord>) -> Color {
+        let h = match h {
+            Some(h) => h,
+            None => return Color::zero(),
+        };
+        match self {
+            Aov::Normal => h.mat.shading_normal(r, h).apply(|x| x as f32),
+            Aov::Albedo => h.mat.albedo(h),
+            Aov::Depth => Color::one() * (h.t * r.dir.length()) as f32,
+            Aov::Position => h.p.apply(|x| x as f32),
+            Aov::Material => Color::one() * material_id(h.mat.get_type()) as f32,
+            Aov::ObjectId => Color::one() * h.object_id as f32,
+        }
+    }
+
+    /// Map the raw AOV buffer to a picture that can be viewed as an ordinary image.
+    pub fn visualize(&self, p: &Picture) -> Picture {
+        let data = match self {
+            Aov::Normal => p.data.iter().map(|c| (*c + 1.0) * 0.5).collect(),
+            Aov::Albedo => p.data.clone(),
+            Aov::Depth | Aov::Position => {
+                // normalize each channel to [0, 1]
+                let mut min = Color::one() * f32::INFINITY;
+                let mut max = Color::one() * f32::NEG_INFINITY;
+                for c in &p.data {
+                    min = Color::new(min.x.min(c.x), min.y.min(c.y), min.z.min(c.z));
+                    max = Color::new(max.x.max(c.x), max.y.max(c.y), max.z.max(c.z));
+                }
+                let range = (max - min).apply(|x| if x > 0.0 { x } else { 1.0 });
+                p.data.iter().map(|c| (*c - min) / range).collect()
+            }
+            Aov::Material | Aov::ObjectId => p.data.iter().map(|c| id_color(c.x)).collect(),
+        };
+        Picture {
+            width: p.width,
+            height: p.height,
+            data,
+            alpha: None,
+        }
+    }
+}
+
+/// 24-bit FNV-1a hash of the material type, so that it can be stored exactly in a `f32`.
+fn material_id(name: &str) -> u32 {
+    let mut hash: u32 = 0x811c_9dc5;
+    for b in name.bytes() {
+        hash ^= b as u32;
+        hash = hash.wrapping_mul(0x0100_0193);
+    }
+    // never collide with misses, which are recorded as zero
+    (hash & 0x00ff_ffff).max(1)
+}
+
+/// Pick a distinguishable pseudo-random color for an id. Id zero(nothing hit) is black.
+fn id_color(id: f32) -> Color {
+    if id == 0.0 {
+        return Color::zero();
+    }
+    let mut h = (id as u32).wrapping_mul(0x9e37_79b9);
+    h ^= h >> 15;
+    h = h.wrapping_mul(0x85eb_ca6b);
+    h ^= h >> 13;
+    Color::new(
+        0.2 + 0.8 * (h & 0xff) as f32 / 255.0,
+        0.2 + 0.8 * ((h >> 8) & 0xff) as f32 / 255.0,
+        0.2 + 0.8 * ((h >> 16) & 0xff) as f32 / 255.0,
+    )
+}
+
+/// AOV pictures produced by a render, in the order they were requested.
+#[derive(Default)]
+pub struct AovBuffers {
+    pub buffers: Vec<(Aov, Picture)>,
+}
+
+impl AovBuffers {
+    pub fn get(&self, aov: Aov) -> Option<&Picture> {
+        self.buffers.iter().find(|(a, _)| *a == aov).map(|(_, p)| p)
+    }
+}
@@ -1,22 +1,112 @@
+//! Cameras: map film coordinates to primary rays.
+//!
+//! Film coordinates `u` & `v` are both in [0, 1], from the bottom left corner of the picture.
+//! Every camera is placed by a `CameraFrame`, so the same pose can be viewed through
+//! different projections.
+
+use std::f64::consts::PI;
+use std::sync::Arc;
+
 use rand::distributions::{Distribution, Uniform};
 use rand::thread_rng;
 
+use crate::render::lens::ApertureShape;
 use crate::utils::{Ray, Vec3};
 
-#[allow(dead_code)]
-pub struct Camera {
+pub trait Camera {
+    /// Get the primary ray through film position (`u`, `v`), or `None` if the position is not
+    /// covered by the projection, like the corners of a circular fisheye.
+    fn get_ray(&self, u: f64, v: f64) -> Option<Ray>;
+    /// The width / height ratio of the picture the camera is designed for.
+    fn get_aspect_ratio(&self) -> f64;
+    /// Get the cone traced by the rays of one pixel in a picture `height` pixels high: its width
+    /// at the camera in world units & how fast it widens with distance in radians. Textures are
+    /// filtered over the width of the cone where it hits, and a zero cone turns that off.
+    fn pixel_cone(&self, _height: usize) -> (f64, f64) {
+        (0.0, 0.0)
+    }
+    fn get_frame(&self) -> &CameraFrame;
+    /// Get a copy of the camera with the shutter open from `t0` to `t1`, e.g. for a frame of
+    /// an animation.
+    fn with_shutter(&self, t0: f64, t1: f64) -> CameraObject;
+    /// Get the camera as a perspective camera, e.g. to build a stereo rig around it.
+    fn as_perspective(&self) -> Option<&PerspectiveCamera> {
+        None
+    }
+}
+
+/// Thread-safe, read-only objects that implement `Camera` trait
+pub type CameraObject = Arc<dyn Camera + Send + Sync>;
+
+pub fn make_camera(c: impl Camera + Send + Sync + 'static) -> CameraObject {
+    Arc::new(c)
+}
+
+/// Position, orientation & shutter interval of a camera.
+///
+/// `u` points right, `v` points up & `w` is the viewing direction, all unit vectors.
+#[derive(Clone)]
+pub struct CameraFrame {
+    pub origin: Vec3<f64>,
+    pub u: Vec3<f64>,
+    pub v: Vec3<f64>,
+    pub w: Vec3<f64>,
+    t_range: Uniform<f64>,
+}
+
+impl CameraFrame {
+    pub fn look_at(
+        origin: Vec3<f64>,
+        look_at: Vec3<f64>,
+        v_up: Vec3<f64>,
+        t0: f64,
+        t1: f64,
+    ) -> Self {
+        let w = (look_at - origin).unit_vector();
+        let u = w.cross(v_up).unit_vector();
+        let v = u.cross(w);
+        CameraFrame {
+            origin,
+            u,
+            v,
+            w,
+            t_range: Uniform::new_inclusive(t0, t1),
+        }
+    }
+
+    pub fn set_shutter(&mut self, t0: f64, t1: f64) {
+        self.t_range = Uniform::new_inclusive(t0, t1);
+    }
+
+    /// Sample a time in the shutter interval.
+    #[inline(always)]
+    pub fn sample_time(&self) -> f64 {
+        self.t_range.sample(&mut thread_rng())
+    }
+
+    /// Turn a direction in camera space, (right, up, forward), into world space.
+    #[inline(always)]
+    fn to_world(&self, x: f64, y: f64, z: f64) -> Vec3<f64> {
+        self.u * x + self.v * y + self.w * z
+    }
+}
+
+/// Pinhole / thin lens camera with perspective projection.
+#[derive(Clone)]
+pub struct PerspectiveCamera {
     pub start_corner: Vec3<f64>,
     pub horizontal: Vec3<f64>,
     pub vertical: Vec3<f64>,
     pub origin: Vec3<f64>,
     lens_radius: f64,
-    u: Vec3<f64>,
-    v: Vec3<f64>,
-    w: Vec3<f64>, // this field is never used, actually
-    t_range: Uniform<f64>,
+    aperture: ApertureShape,
+    vfov: f64,
+    focus_dist: f64,
+    frame: CameraFrame,
 }
 
-impl Camera {
+impl PerspectiveCamera {
+    #[allow(clippy::too_many_arguments)]
     pub fn look_from(
         origin: Vec3<f64>,
         look_at: Vec3<f64>,
@@ -27,27 +117,66 @@ impl Camera {
         focus_dist: f64,
         t0: f64,
         t1: f64,
-    ) -> Camera {
-        let theta = vfov * std::f64::consts::PI / 180.0;
+    ) -> PerspectiveCamera {
+        let frame = CameraFrame::look_at(origin, look_at, v_up, t0, t1);
+        Self::from_frame(frame, vfov, aspect, aperture, focus_dist)
+    }
+
+    /// Create a camera at the given frame, with vertical field of view `vfov` in degrees.
+    pub fn from_frame(
+        frame: CameraFrame,
+        vfov: f64,
+        aspect: f64,
+        aperture: f64,
+        focus_dist: f64,
+    ) -> PerspectiveCamera {
+        let theta = vfov * PI / 180.0;
         let half_height = (theta / 2.0).tan();
         let half_width = half_height * aspect;
-
-        let w = (look_at - origin).unit_vector();
-        let u = w.cross(v_up).unit_vector();
-        let v = u.cross(w);
-        Camera {
-            start_corner: origin + (w - u * half_width - v * half_height) * focus_dist,
+        let (u, v, w) = (frame.u, frame.v, frame.w);
+        PerspectiveCamera {
+            start_corner: frame.origin + (w - u * half_width - v * half_height) * focus_dist,
             horizontal: u * (2.0 * half_width * focus_dist),
             vertical: v * (2.0 * half_height * focus_dist),
-            origin,
+            origin: frame.origin,
             lens_radius: aperture / 2.0,
-            u,
-            v,
-            w,
-            t_range: Uniform::new(t0, t1),
+            aperture: ApertureShape::Circle,
+            vfov,
+            focus_dist,
+            frame,
         }
     }
 
+    pub fn get_vfov(&self) -> f64 {
+        self.vfov
+    }
+
+    pub fn get_focus_distance(&self) -> f64 {
+        self.focus_dist
+    }
+
+    pub fn get_aperture(&self) -> f64 {
+        self.lens_radius * 2.0
+    }
+
+    /// Move the camera by `offset` without turning it.
+    pub fn translate(&mut self, offset: Vec3<f64>) {
+        self.origin += offset;
+        self.start_corner += offset;
+        self.frame.origin += offset;
+    }
+
+    /// Shift the film window sideways without turning the camera, by `x` to the right & `y`
+    /// upwards in world units on the focus plane. This gives off-axis projections.
+    pub fn shift_window(&mut self, x: f64, y: f64) {
+        self.start_corner += self.frame.u * x + self.frame.v * y;
+    }
+
+    /// Set the shape of the lens aperture, which shapes the bokeh.
+    pub fn set_aperture_shape(&mut self, shape: ApertureShape) {
+        self.aperture = shape;
+    }
+
     #[inline(always)]
     pub fn get_rand_in_unit_disk() -> Vec3<f64> {
         let mut rng = thread_rng();
@@ -59,21 +188,218 @@ impl Camera {
             }
         }
     }
+}
 
+impl Camera for PerspectiveCamera {
     #[inline(always)]
-    pub fn get_ray(&self, u: f64, v: f64) -> Ray {
-        let r = Self::get_rand_in_unit_disk() * self.lens_radius;
-        let offset = self.u * r.x + self.v * r.y;
+    fn get_ray(&self, u: f64, v: f64) -> Option<Ray> {
+        let (x, y) = self.aperture.sample();
+        let offset = (self.frame.u * x + self.frame.v * y) * self.lens_radius;
         let orig = self.origin + offset;
-        let mut rng = thread_rng();
-        Ray {
+        Some(Ray {
             orig,
             dir: self.start_corner + self.horizontal * u + self.vertical * v - orig,
-            t: self.t_range.sample(&mut rng),
-        }
+            t: self.frame.sample_time(),
+        })
     }
 
-    pub fn get_aspect_ratio(&self) -> f64 {
+    fn get_aspect_ratio(&self) -> f64 {
         self.horizontal.length() / self.vertical.length()
     }
+
+    fn pixel_cone(&self, height: usize) -> (f64, f64) {
+        (
+            0.0,
+            self.vertical.length() / self.focus_dist / height as f64,
+        )
+    }
+
+    fn get_frame(&self) -> &CameraFrame {
+        &self.frame
+    }
+
+    fn with_shutter(&self, t0: f64, t1: f64) -> CameraObject {
+        let mut c = self.clone();
+        c.frame.set_shutter(t0, t1);
+        make_camera(c)
+    }
+
+    fn as_perspective(&self) -> Option<&PerspectiveCamera> {
+        Some(self)
+    }
+}
+
+/// Orthographic camera, casting parallel rays from a rectangle of `height` in world units.
+#[derive(Clone)]
+pub struct OrthographicCamera {
+    pub width: f64,
+    pub height: f64,
+    frame: CameraFrame,
+}
+
+impl OrthographicCamera {
+    pub fn new(frame: CameraFrame, height: f64, aspect: f64) -> Self {
+        OrthographicCamera {
+            width: height * aspect,
+            height,
+            frame,
+        }
+    }
+}
+
+impl Camera for OrthographicCamera {
+    fn get_ray(&self, u: f64, v: f64) -> Option<Ray> {
+        let f = &self.frame;
+        Some(Ray {
+            orig: f.origin + f.u * ((u - 0.5) * self.width) + f.v * ((v - 0.5) * self.height),
+            dir: f.w,
+            t: f.sample_time(),
+        })
+    }
+
+    fn get_aspect_ratio(&self) -> f64 {
+        self.width / self.height
+    }
+
+    fn pixel_cone(&self, height: usize) -> (f64, f64) {
+        (self.height / height as f64, 0.0)
+    }
+
+    fn get_frame(&self) -> &CameraFrame {
+        &self.frame
+    }
+
+    fn with_shutter(&self, t0: f64, t1: f64) -> CameraObject {
+        let mut c = self.clone();
+        c.frame.set_shutter(t0, t1);
+        make_camera(c)
+    }
+}
+
+/// Mapping from the angle to the viewing direction to the distance from the image center.
+#[derive(Debug, Copy, Clone)]
+pub enum FisheyeProjection {
+    /// Distance proportional to the angle.
+    Equidistant,
+    /// Distance proportional to `sin(theta / 2)`, preserving solid angles.
+    Equisolid,
+}
+
+/// Circular fisheye camera, whose image circle touches the top & bottom of the picture.
+///
+/// * `fov`: Angle covered by the diameter of the image circle in degrees, up to 360.
+#[derive(Clone)]
+pub struct FisheyeCamera {
+    pub fov: f64,
+    pub aspect: f64,
+    pub projection: FisheyeProjection,
+    frame: CameraFrame,
+}
+
+impl FisheyeCamera {
+    pub fn new(frame: CameraFrame, fov: f64, aspect: f64, projection: FisheyeProjection) -> Self {
+        assert!(
+            fov > 0.0 && fov <= 360.0,
+            "Fisheye fov should be in (0, 360]."
+        );
+        FisheyeCamera {
+            fov,
+            aspect,
+            projection,
+            frame,
+        }
+    }
+}
+
+impl Camera for FisheyeCamera {
+    fn get_ray(&self, u: f64, v: f64) -> Option<Ray> {
+        // position relative to the image circle, whose radius is 1
+        let x = (2.0 * u - 1.0) * self.aspect;
+        let y = 2.0 * v - 1.0;
+        let r = (x * x + y * y).sqrt();
+        if r > 1.0 {
+            return None;
+        }
+        let half_fov = self.fov * PI / 360.0;
+        let theta = match self.projection {
+            FisheyeProjection::Equidistant => r * half_fov,
+            FisheyeProjection::Equisolid => 2.0 * (r * (half_fov / 2.0).sin()).asin(),
+        };
+        let (sin_theta, cos_theta) = theta.sin_cos();
+        let (cos_phi, sin_phi) = if r > 0.0 { (x / r, y / r) } else { (1.0, 0.0) };
+        let f = &self.frame;
+        Some(Ray {
+            orig: f.origin,
+            dir: f.to_world(sin_theta * cos_phi, sin_theta * sin_phi, cos_theta),
+            t: f.sample_time(),
+        })
+    }
+
+    fn get_aspect_ratio(&self) -> f64 {
+        self.aspect
+    }
+
+    /// The cone of pixels at the center of the image circle.
+    fn pixel_cone(&self, height: usize) -> (f64, f64) {
+        (0.0, self.fov.to_radians() / height as f64)
+    }
+
+    fn get_frame(&self) -> &CameraFrame {
+        &self.frame
+    }
+
+    fn with_shutter(&self, t0: f64, t1: f64) -> CameraObject {
+        let mut c = self.clone();
+        c.frame.set_shutter(t0, t1);
+        make_camera(c)
+    }
+}
+
+/// 360° panoramic camera with equirectangular projection, e.g. for VR.
+///
+/// Longitude spans the width & latitude the height of the picture, with the viewing direction
+/// at the center, so the picture should be twice as wide as it is high.
+#[derive(Clone)]
+pub struct EquirectangularCamera {
+    frame: CameraFrame,
+}
+
+impl EquirectangularCamera {
+    pub fn new(frame: CameraFrame) -> Self {
+        EquirectangularCamera { frame }
+    }
+}
+
+impl Camera for EquirectangularCamera {
+    fn get_ray(&self, u: f64, v: f64) -> Option<Ray> {
+        let phi = (u - 0.5) * 2.0 * PI;
+        let lat = (v - 0.5) * PI;
+        let (sin_phi, cos_phi) = phi.sin_cos();
+        let (sin_lat, cos_lat) = lat.sin_cos();
+        let f = &self.frame;
+        Some(Ray {
+            orig: f.origin,
+            dir: f.to_world(cos_lat * sin_phi, sin_lat, cos_lat * cos_phi),
+            t: f.sample_time(),
+        })
+    }
+
+    fn get_aspect_ratio(&self) -> f64 {
+        2.0
+    }
+
+    /// The cone of pixels on the horizon.
+    fn pixel_cone(&self, height: usize) -> (f64, f64) {
+        (0.0, PI / height as f64)
+    }
+
+    fn get_frame(&self) -> &CameraFrame {
+        &self.frame
+    }
+
+    fn with_shutter(&self, t0: f64, t1: f64) -> CameraObject {
+        let mut c = self.clone();
+        c.frame.set_shutter(t0, t1);
+        make_camera(c)
+    }
 }
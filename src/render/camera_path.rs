@@ -0,0 +1,88 @@
+//! Camera path: a perspective camera animated by keyframes.
+
+use crate::render::camera::{make_camera, Camera, CameraFrame, CameraObject, PerspectiveCamera};
+use crate::utils::keyframe::Keyframes;
+use crate::utils::{Ray, Vec3};
+
+/// Perspective camera whose position, target & field of view follow keyframes.
+///
+/// Every ray is traced from the camera at the time of the ray, so the camera motion is blurred
+/// over the shutter interval. The camera focuses on its target.
+#[derive(Clone)]
+pub struct CameraPath {
+    pub origin: Keyframes<Vec3<f64>>,
+    pub look_at: Keyframes<Vec3<f64>>,
+    /// Vertical field of view in degrees.
+    pub vfov: Keyframes<f64>,
+    pub v_up: Vec3<f64>,
+    pub aspect: f64,
+    pub aperture: f64,
+    /// Pose at the time the shutter opens.
+    frame: CameraFrame,
+}
+
+impl CameraPath {
+    pub fn new(
+        origin: Keyframes<Vec3<f64>>,
+        look_at: Keyframes<Vec3<f64>>,
+        vfov: Keyframes<f64>,
+        v_up: Vec3<f64>,
+        aspect: f64,
+        aperture: f64,
+    ) -> Self {
+        let frame = CameraFrame::look_at(origin.at(0.0), look_at.at(0.0), v_up, 0.0, 0.0);
+        CameraPath {
+            origin,
+            look_at,
+            vfov,
+            v_up,
+            aspect,
+            aperture,
+            frame,
+        }
+    }
+
+    /// Get the still camera at `time`.
+    pub fn camera_at(&self, time: f64) -> PerspectiveCamera {
+        let origin = self.origin.at(time);
+        let look_at = self.look_at.at(time);
+        PerspectiveCamera::look_from(
+            origin,
+            look_at,
+            self.v_up,
+            self.vfov.at(time),
+            self.aspect,
+            self.aperture,
+            (look_at - origin).length(),
+            time,
+            time,
+        )
+    }
+}
+
+impl Camera for CameraPath {
+    fn get_ray(&self, u: f64, v: f64) -> Option<Ray> {
+        let time = self.frame.sample_time();
+        let mut r = self.camera_at(time).get_ray(u, v)?;
+        r.t = time;
+        Some(r)
+    }
+
+    fn get_aspect_ratio(&self) -> f64 {
+        self.aspect
+    }
+
+    fn pixel_cone(&self, height: usize) -> (f64, f64) {
+        self.camera_at(self.frame.sample_time()).pixel_cone(height)
+    }
+
+    fn get_frame(&self) -> &CameraFrame {
+        &self.frame
+    }
+
+    fn with_shutter(&self, t0: f64, t1: f64) -> CameraObject {
+        let mut c = self.clone();
+        c.frame = CameraFrame::look_at(self.origin.at(t0), self.look_at.at(t0), self.v_up, t0, t1);
+        make_camera(c)
+    }
+}
@@ -0,0 +1,148 @@
+//! Denoiser: edge-avoiding À-trous wavelet filter guided by AOVs.
+//!
+//! Follows "Edge-Avoiding À-Trous Wavelet Transform for fast Global Illumination Filtering"
+//! (Dammertz et al. 2010). A 5 * 5 B3-spline kernel is applied repeatedly with growing holes
+//! between its taps, and every tap is weighted by how similar its color, normal, depth & albedo are
+//! to the center pixel's, so that the blur stops at geometric & texture edges.
+//!
+//! The filter runs on the linear picture, before gamma correction.
+
+use crate::render::aov::{Aov, AovBuffers};
+use crate::render::filter::Filter;
+use crate::utils::{Color, Picture};
+
+/// B3-spline kernel, separable.
+const KERNEL: [f32; 5] = [1.0 / 16.0, 1.0 / 4.0, 3.0 / 8.0, 1.0 / 4.0, 1.0 / 16.0];
+
+/// Strength parameters of the denoiser. Larger sigma values blur across larger differences.
+#[derive(Debug, Copy, Clone)]
+pub struct DenoiseParams {
+    /// Number of À-trous passes, the kernel footprint is `4 * 2^iterations + 1` pixels wide.
+    pub iterations: usize,
+    /// Color sensitivity, halved after every pass.
+    pub sigma_color: f32,
+    /// Normal sensitivity, in squared distance between unit normals.
+    pub sigma_normal: f32,
+    /// Depth sensitivity, relative to the depth of the center pixel.
+    pub sigma_depth: f32,
+    /// Albedo sensitivity.
+    pub sigma_albedo: f32,
+}
+
+impl Default for DenoiseParams {
+    fn default() -> Self {
+        DenoiseParams {
+            iterations: 5,
+            sigma_color: 4.0,
+            sigma_normal: 0.1,
+            sigma_depth: 0.05,
+            sigma_albedo: 0.3,
+        }
+    }
+}
+
+impl DenoiseParams {
+    /// The AOVs the denoiser needs as feature buffers.
+    pub const FEATURES: [Aov; 3] = [Aov::Albedo, Aov::Normal, Aov::Depth];
+}
+
+pub struct AtrousDenoiser<'a> {
+    pub params: DenoiseParams,
+    pub albedo: &'a Picture,
+    pub normal: &'a Picture,
+    pub depth: &'a Picture,
+}
+
+impl<'a> AtrousDenoiser<'a> {
+    /// Build a denoiser from rendered AOVs, which must contain all of `DenoiseParams::FEATURES`.
+    pub fn from_aovs(params: DenoiseParams, aovs: &'a AovBuffers) -> Option<Self> {
+        Some(AtrousDenoiser {
+            params,
+            albedo: aovs.get(Aov::Albedo)?,
+            normal: aovs.get(Aov::Normal)?,
+            depth: aovs.get(Aov::Depth)?,
+        })
+    }
+
+    /// Albedo used to demodulate the picture, so that texture details are not blurred away.
+    #[inline(always)]
+    fn demodulation_factor(a: Color) -> Color {
+        a.apply(|x| if x > 0.01 { x } else { 1.0 })
+    }
+
+    /// Run a single À-trous pass with holes of `step` pixels between kernel taps.
+    fn pass(&self, src: &Picture, dst: &mut Picture, step: usize, sigma_color: f32) {
+        let (w, h) = (src.width as isize, src.height as isize);
+        let inv_color = 1.0 / (sigma_color * sigma_color).max(1e-8);
+        let inv_normal = 1.0 / self.params.sigma_normal.max(1e-8);
+        let inv_depth = 1.0 / self.params.sigma_depth.max(1e-8);
+        let inv_albedo = 1.0 / (self.params.sigma_albedo * self.params.sigma_albedo).max(1e-8);
+        for y in 0..h {
+            for x in 0..w {
+                let p = (y * w + x) as usize;
+                let (cp, np, zp, ap) = (
+                    src.data[p],
+                    self.normal.data[p],
+                    self.depth.data[p].x,
+                    self.albedo.data[p],
+                );
+                let mut sum = Color::zero();
+                let mut weight_sum = 0.0;
+                for (dy, ky) in KERNEL.iter().enumerate() {
+                    let qy = y + (dy as isize - 2) * step as isize;
+                    if qy < 0 || qy >= h {
+                        continue;
+                    }
+                    for (dx, kx) in KERNEL.iter().enumerate() {
+                        let qx = x + (dx as isize - 2) * step as isize;
+                        if qx < 0 || qx >= w {
+                            continue;
+                        }
+                        let q = (qy * w + qx) as usize;
+                        let cq = src.data[q];
+                        let w_color = (-(cp - cq).length_square() * inv_color).exp();
+                        let w_normal =
+                            (-(np - self.normal.data[q]).length_square() * inv_normal).exp();
+                        let dz = (zp - self.depth.data[q].x).abs() / zp.abs().max(1e-4);
+                        let w_depth = (-dz * inv_depth).exp();
+                        let w_albedo =
+                            (-(ap - self.albedo.data[q]).length_square() * inv_albedo).exp();
+                        let weight = ky * kx * w_color * w_normal * w_depth * w_albedo;
+                        sum += cq * weight;
+                        weight_sum += weight;
+                    }
+                }
+                // the center tap always has a positive weight
+                dst.data[p] = sum / weight_sum;
+            }
+        }
+    }
+}
+
+impl<'a> Filter for AtrousDenoiser<'a> {
+    fn filter(&self, p: &mut Picture) {
+        assert_eq!(p.data.len(), self.albedo.data.len());
+        assert_eq!(p.data.len(), self.normal.data.len());
+        assert_eq!(p.data.len(), self.depth.data.len());
+        let factors: Vec<Color> = self
+            .albedo
+            .data
+            .iter()
+            .map(|a| Self::demodulation_factor(*a))
+            .collect();
+        let mut src = Picture::new(p.width, p.height);
+        for ((s, c), f) in src.data.iter_mut().zip(&p.data).zip(&factors) {
+            *s = *c / *f;
+        }
+        let mut dst = Picture::new(p.width, p.height);
+        let mut sigma_color = self.params.sigma_color;
+        for i in 0..self.params.iterations {
+            self.pass(&src, &mut dst, 1 << i, sigma_color);
+            std::mem::swap(&mut src, &mut dst);
+            sigma_color *= 0.5;
+        }
+        for ((c, s), f) in p.data.iter_mut().zip(&src.data).zip(&factors) {
+            *c = *s * *f;
+        }
+    }
+}
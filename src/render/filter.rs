@@ -1,6 +1,6 @@
 //! Filter: define filters for rendering.
 
-use crate::utils::Picture;
+use crate::utils::{Color, Picture};
 
 pub trait Filter {
     fn filter(&self, p: &mut Picture);
@@ -20,3 +20,65 @@ impl Filter for GammaFilter {
         }
     }
 }
+
+/// Luminance of a linear color, with Rec. 709 primaries.
+#[inline(always)]
+pub fn luminance(c: &Color) -> f32 {
+    0.2126 * c.x + 0.7152 * c.y + 0.0722 * c.z
+}
+
+/// Outlier rejection: remove fireflies by comparing every pixel against its neighborhood.
+///
+/// A pixel is an outlier if its luminance exceeds the mean of its neighbors by more than
+/// `threshold` standard deviations. Outliers are replaced by the mean of their neighbors.
+pub struct OutlierRejectionFilter {
+    /// Radius of the square neighborhood in pixels, excluding the pixel itself.
+    pub radius: usize,
+    pub threshold: f32,
+}
+
+impl Default for OutlierRejectionFilter {
+    fn default() -> Self {
+        OutlierRejectionFilter {
+            radius: 1,
+            threshold: 3.0,
+        }
+    }
+}
+
+impl Filter for OutlierRejectionFilter {
+    fn filter(&self, p: &mut Picture) {
+        let lum: Vec<f32> = p.data.iter().map(luminance).collect();
+        let mut result = p.data.clone();
+        let r = self.radius as isize;
+        let (w, h) = (p.width as isize, p.height as isize);
+        for y in 0..h {
+            for x in 0..w {
+                let mut sum = Color::zero();
+                let (mut n, mut l_sum, mut l_sq_sum) = (0.0_f32, 0.0_f32, 0.0_f32);
+                for qy in (y - r).max(0)..(y + r + 1).min(h) {
+                    for qx in (x - r).max(0)..(x + r + 1).min(w) {
+                        if qx == x && qy == y {
+                            continue;
+                        }
+                        let q = (qy * w + qx) as usize;
+                        sum += p.data[q];
+                        l_sum += lum[q];
+                        l_sq_sum += lum[q] * lum[q];
+                        n += 1.0;
+                    }
+                }
+                if n == 0.0 {
+                    continue;
+                }
+                let mean = l_sum / n;
+                let std_dev = (l_sq_sum / n - mean * mean).max(0.0).sqrt();
+                let idx = (y * w + x) as usize;
+                if lum[idx] > mean + self.threshold * std_dev && lum[idx] > mean {
+                    result[idx] = sum / n;
+                }
+            }
+        }
+        p.data = result;
+    }
+}
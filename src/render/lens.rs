@@ -0,0 +1,419 @@
+//! Lens: aperture shapes & a camera tracing rays through a system of lens elements.
+//!
+//! The lens system follows the usual lens prescription layout: elements are listed from the
+//! scene side to the film side, each with the curvature radius of its surface, the distance
+//! to the next surface, the index of refraction behind it & its aperture diameter. A surface
+//! with zero curvature radius is the aperture stop.
+
+use std::f64::consts::PI;
+use std::sync::Arc;
+
+use rand::{thread_rng, Rng};
+
+use crate::render::camera::{make_camera, Camera, CameraFrame, CameraObject};
+use crate::render::filter::luminance;
+use crate::utils::{Picture, Ray, Vec3};
+
+/// Aperture mask from a grayscale image, brighter pixels letting more light through.
+///
+/// The image is stretched over [-1, 1] * [-1, 1], and sampled proportionally to brightness.
+pub struct ApertureMask {
+    width: usize,
+    height: usize,
+    transmission: Vec<f64>,
+    cdf: Vec<f64>,
+}
+
+impl ApertureMask {
+    pub fn from_picture(p: &Picture) -> Result<Self, &'static str> {
+        let transmission: Vec<f64> = p.data.iter().map(|c| luminance(c) as f64).collect();
+        let mut cdf = Vec::with_capacity(transmission.len());
+        let mut sum = 0.0;
+        for t in &transmission {
+            sum += t.max(0.0);
+            cdf.push(sum);
+        }
+        if sum <= 0.0 {
+            return Err("Aperture mask is completely opaque.");
+        }
+        cdf.iter_mut().for_each(|x| *x /= sum);
+        Ok(ApertureMask {
+            width: p.width,
+            height: p.height,
+            transmission,
+            cdf,
+        })
+    }
+
+    fn sample(&self) -> (f64, f64) {
+        let mut rng = thread_rng();
+        let xi: f64 = rng.gen();
+        let idx = self
+            .cdf
+            .partition_point(|c| *c <= xi)
+            .min(self.cdf.len() - 1);
+        let (i, j) = (idx % self.width, idx / self.width);
+        let x = (i as f64 + rng.gen::<f64>()) / self.width as f64;
+        let y = (j as f64 + rng.gen::<f64>()) / self.height as f64;
+        // rows are counted from the top of the image
+        (2.0 * x - 1.0, 1.0 - 2.0 * y)
+    }
+
+    fn transmission(&self, x: f64, y: f64) -> f64 {
+        if x.abs() > 1.0 || y.abs() > 1.0 {
+            return 0.0;
+        }
+        let i = (((x + 1.0) / 2.0 * self.width as f64) as usize).min(self.width - 1);
+        let j = (((1.0 - y) / 2.0 * self.height as f64) as usize).min(self.height - 1);
+        self.transmission[j * self.width + i]
+    }
+}
+
+/// Shape of an aperture of unit radius, which shapes the bokeh of out-of-focus highlights.
+#[derive(Clone, Default)]
+pub enum ApertureShape {
+    #[default]
+    Circle,
+    /// Regular polygon formed by `blades` straight blades, rotated by `rotation` in degrees.
+    Polygon {
+        blades: usize,
+        rotation: f64,
+    },
+    Mask(Arc<ApertureMask>),
+}
+
+impl ApertureShape {
+    pub fn polygon(blades: usize, rotation: f64) -> Self {
+        assert!(blades >= 3, "A bladed aperture needs at least 3 blades.");
+        ApertureShape::Polygon { blades, rotation }
+    }
+
+    /// Sample a point uniformly on the open part of the aperture.
+    pub fn sample(&self) -> (f64, f64) {
+        let mut rng = thread_rng();
+        match self {
+            ApertureShape::Circle => {
+                let r = rng.gen::<f64>().sqrt();
+                let theta = rng.gen_range(0.0, 2.0 * PI);
+                (r * theta.cos(), r * theta.sin())
+            }
+            ApertureShape::Polygon { blades, rotation } => {
+                // pick one of the triangles between the center & two adjacent corners
+                let step = 2.0 * PI / *blades as f64;
+                let k = rng.gen_range(0, *blades) as f64;
+                let a0 = rotation.to_radians() + k * step;
+                let a1 = a0 + step;
+                let (mut s, mut t): (f64, f64) = (rng.gen(), rng.gen());
+                if s + t > 1.0 {
+                    s = 1.0 - s;
+                    t = 1.0 - t;
+                }
+                (s * a0.cos() + t * a1.cos(), s * a0.sin() + t * a1.sin())
+            }
+            ApertureShape::Mask(m) => m.sample(),
+        }
+    }
+
+    /// Whether the point is in the open part of the aperture, randomly for partial masks.
+    pub fn contains(&self, x: f64, y: f64) -> bool {
+        let r2 = x * x + y * y;
+        match self {
+            ApertureShape::Circle => r2 <= 1.0,
+            ApertureShape::Polygon { blades, rotation } => {
+                let step = 2.0 * PI / *blades as f64;
+                let theta = (y.atan2(x) - rotation.to_radians()).rem_euclid(2.0 * PI);
+                // angle to the bisector of the sector, whose edge is at the apothem
+                let alpha = theta - ((theta / step).floor() + 0.5) * step;
+                r2.sqrt() * alpha.cos() <= (step / 2.0).cos()
+            }
+            ApertureShape::Mask(m) => thread_rng().gen::<f64>() < m.transmission(x, y),
+        }
+    }
+}
+
+/// A spherical surface of a lens system, or the aperture stop if `curvature_radius` is zero.
+///
+/// * `curvature_radius`: Positive when the center of curvature is on the film side.
+/// * `thickness`: Distance along the axis to the next surface towards the film.
+/// * `ior`: Index of refraction of the material behind the surface, 1 for air.
+/// * `aperture_radius`: Radius of the clear aperture of the surface.
+#[derive(Debug, Copy, Clone)]
+pub struct LensElement {
+    pub curvature_radius: f64,
+    pub thickness: f64,
+    pub ior: f64,
+    pub aperture_radius: f64,
+}
+
+impl LensElement {
+    #[inline(always)]
+    fn is_stop(&self) -> bool {
+        self.curvature_radius == 0.0
+    }
+}
+
+fn refract(wi: Vec3<f64>, n: Vec3<f64>, eta: f64) -> Option<Vec3<f64>> {
+    let cos_theta_i = n.dot(wi);
+    let sin2_theta_i = (1.0 - cos_theta_i * cos_theta_i).max(0.0);
+    let sin2_theta_t = eta * eta * sin2_theta_i;
+    if sin2_theta_t >= 1.0 {
+        return None;
+    }
+    let cos_theta_t = (1.0 - sin2_theta_t).sqrt();
+    Some(-wi * eta + n * (eta * cos_theta_i - cos_theta_t))
+}
+
+/// Camera simulating a real lens, traced surface by surface from the film.
+///
+/// Lens space has the film at z = 0 and the scene towards -z; lengths are in the units of the
+/// lens description, usually millimeters, and `unit` converts them to scene units.
+/// Rays blocked inside the lens are lost, which gives natural vignetting, and the curved
+/// surfaces produce the distortion of the real lens.
+#[derive(Clone)]
+pub struct LensSystemCamera {
+    elements: Vec<LensElement>,
+    stop_shape: ApertureShape,
+    film_width: f64,
+    film_height: f64,
+    unit: f64,
+    frame: CameraFrame,
+}
+
+impl LensSystemCamera {
+    /// Create a lens camera focused at `focus_distance` in scene units.
+    ///
+    /// * `film_diagonal`: Diagonal of the film, in lens units.
+    /// * `unit`: Size of a lens unit in scene units.
+    pub fn new(
+        frame: CameraFrame,
+        elements: Vec<LensElement>,
+        film_diagonal: f64,
+        aspect: f64,
+        focus_distance: f64,
+        unit: f64,
+    ) -> Result<Self, &'static str> {
+        if elements.is_empty() {
+            return Err("Lens system has no elements.");
+        }
+        let film_height = film_diagonal / (1.0 + aspect * aspect).sqrt();
+        let mut camera = LensSystemCamera {
+            elements,
+            stop_shape: ApertureShape::Circle,
+            film_width: film_height * aspect,
+            film_height,
+            unit,
+            frame,
+        };
+        let film_distance = camera.focus(focus_distance / unit)?;
+        camera.elements.last_mut().unwrap().thickness = film_distance;
+        Ok(camera)
+    }
+
+    /// Set the shape of the aperture stop, keeping its radius.
+    pub fn set_stop_shape(&mut self, shape: ApertureShape) {
+        self.stop_shape = shape;
+    }
+
+    /// Scale the aperture stop to the given diameter, e.g. to stop the lens down.
+    pub fn set_stop_diameter(&mut self, diameter: f64) -> Result<(), &'static str> {
+        let stop = self
+            .elements
+            .iter_mut()
+            .find(|e| e.is_stop())
+            .ok_or("Lens system has no aperture stop.")?;
+        if diameter <= 0.0 || diameter > 2.0 * stop.aperture_radius {
+            return Err("Stop diameter should be positive & no larger than the stop.");
+        }
+        stop.aperture_radius = diameter / 2.0;
+        Ok(())
+    }
+
+    fn front_z(&self) -> f64 {
+        -self.elements.iter().map(|e| e.thickness).sum::<f64>()
+    }
+
+    fn rear_z(&self) -> f64 {
+        -self.elements.last().unwrap().thickness
+    }
+
+    /// Intersect a spherical surface, returning the ray parameter & the normal facing the ray.
+    fn intersect_surface(
+        radius: f64,
+        z_center: f64,
+        orig: Vec3<f64>,
+        dir: Vec3<f64>,
+    ) -> Option<(f64, Vec3<f64>)> {
+        let o = orig - Vec3::new(0.0, 0.0, z_center);
+        let a = dir.length_square();
+        let b = 2.0 * dir.dot(o);
+        let c = o.length_square() - radius * radius;
+        let delta = b * b - 4.0 * a * c;
+        if delta < 0.0 {
+            return None;
+        }
+        let sq = delta.sqrt();
+        let (t0, t1) = ((-b - sq) / (2.0 * a), (-b + sq) / (2.0 * a));
+        // the surface is the half of the sphere facing the axis direction of the ray
+        let t = if (dir.z > 0.0) ^ (radius < 0.0) {
+            t0.min(t1)
+        } else {
+            t0.max(t1)
+        };
+        if t < 0.0 {
+            return None;
+        }
+        let n = (o + dir * t).unit_vector();
+        let n = if n.dot(dir) > 0.0 { -n } else { n };
+        Some((t, n))
+    }
+
+    /// Pass a ray through the surface `i` located at `z`, refracting from `eta_i` to `eta_t`.
+    fn pass_surface(
+        &self,
+        i: usize,
+        z: f64,
+        (orig, dir): (Vec3<f64>, Vec3<f64>),
+        eta_i: f64,
+        eta_t: f64,
+    ) -> Option<(Vec3<f64>, Vec3<f64>)> {
+        let e = &self.elements[i];
+        if e.is_stop() {
+            let t = (z - orig.z) / dir.z;
+            if t < 0.0 {
+                return None;
+            }
+            let p = orig + dir * t;
+            let r = e.aperture_radius;
+            if !self.stop_shape.contains(p.x / r, p.y / r) {
+                return None;
+            }
+            return Some((p, dir));
+        }
+        let (t, n) =
+            Self::intersect_surface(e.curvature_radius, z + e.curvature_radius, orig, dir)?;
+        let p = orig + dir * t;
+        if p.x * p.x + p.y * p.y > e.aperture_radius * e.aperture_radius {
+            return None;
+        }
+        let dir = refract(-dir.unit_vector(), n, eta_i / eta_t)?;
+        Some((p, dir))
+    }
+
+    /// Index of refraction in front of the surface `i`, on the scene side.
+    #[inline(always)]
+    fn ior_before(&self, i: usize) -> f64 {
+        if i == 0 || self.elements[i - 1].ior == 0.0 {
+            1.0
+        } else {
+            self.elements[i - 1].ior
+        }
+    }
+
+    #[inline(always)]
+    fn ior_after(&self, i: usize) -> f64 {
+        let ior = self.elements[i].ior;
+        if ior == 0.0 {
+            1.0
+        } else {
+            ior
+        }
+    }
+
+    /// Trace a ray in lens space from the film through every surface to the scene.
+    fn trace_from_film(&self, mut ray: (Vec3<f64>, Vec3<f64>)) -> Option<(Vec3<f64>, Vec3<f64>)> {
+        let mut z = 0.0;
+        for i in (0..self.elements.len()).rev() {
+            z -= self.elements[i].thickness;
+            ray = self.pass_surface(i, z, ray, self.ior_after(i), self.ior_before(i))?;
+        }
+        Some(ray)
+    }
+
+    /// Trace a ray in lens space from the scene through every surface to the film.
+    fn trace_from_scene(&self, mut ray: (Vec3<f64>, Vec3<f64>)) -> Option<(Vec3<f64>, Vec3<f64>)> {
+        let mut z = self.front_z();
+        for i in 0..self.elements.len() {
+            ray = self.pass_surface(i, z, ray, self.ior_before(i), self.ior_after(i))?;
+            z += self.elements[i].thickness;
+        }
+        Some(ray)
+    }
+
+    /// Principal plane & focal point along the axis, from a ray parallel to the axis.
+    fn cardinal_points(r_in: (Vec3<f64>, Vec3<f64>), r_out: (Vec3<f64>, Vec3<f64>)) -> (f64, f64) {
+        let (o, d) = r_out;
+        let tf = -o.x / d.x;
+        let tp = (r_in.0.x - o.x) / d.x;
+        ((o + d * tp).z, (o + d * tf).z)
+    }
+
+    /// Find the distance from the rear surface to the film that focuses at `distance` in lens
+    /// units, by the thick lens approximation.
+    fn focus(&self, distance: f64) -> Result<f64, &'static str> {
+        let err = "Lens system fails to focus paraxial rays.";
+        let x = 0.001
+            * (self.film_width * self.film_width + self.film_height * self.film_height).sqrt();
+        let r_scene = (
+            Vec3::new(x, 0.0, self.front_z() - 1.0),
+            Vec3::new(0.0, 0.0, 1.0),
+        );
+        let r_film = self.trace_from_scene(r_scene).ok_or(err)?;
+        let (pz0, fz0) = Self::cardinal_points(r_scene, r_film);
+        let r_film = (
+            Vec3::new(x, 0.0, self.rear_z() + 1.0),
+            Vec3::new(0.0, 0.0, -1.0),
+        );
+        let r_scene = self.trace_from_film(r_film).ok_or(err)?;
+        let (pz1, _) = Self::cardinal_points(r_film, r_scene);
+        let f = fz0 - pz0;
+        let z = -distance;
+        let c = (pz1 - z - pz0) * (pz1 - z - 4.0 * f - pz0);
+        if f <= 0.0 || c < 0.0 {
+            return Err("Focus distance is too close for the lens system.");
+        }
+        let delta = 0.5 * (pz1 - z + pz0 - c.sqrt());
+        Ok(self.elements.last().unwrap().thickness + delta)
+    }
+}
+
+impl Camera for LensSystemCamera {
+    fn get_ray(&self, u: f64, v: f64) -> Option<Ray> {
+        // the lens flips the image, so the film is mirrored in both directions
+        let p_film = Vec3::new(
+            (0.5 - u) * self.film_width,
+            (0.5 - v) * self.film_height,
+            0.0,
+        );
+        // aim at a uniform point on the rear surface
+        let rear = self.elements.last().unwrap();
+        let (x, y) = ApertureShape::Circle.sample();
+        let p_rear = Vec3::new(
+            x * rear.aperture_radius,
+            y * rear.aperture_radius,
+            self.rear_z(),
+        );
+        let (orig, dir) = self.trace_from_film((p_film, p_rear - p_film))?;
+        // lens space looks towards -z
+        let f = &self.frame;
+        let to_world = |p: Vec3<f64>| f.u * p.x + f.v * p.y - f.w * p.z;
+        Some(Ray {
+            orig: f.origin + to_world(orig) * self.unit,
+            dir: to_world(dir),
+            t: f.sample_time(),
+        })
+    }
+
+    fn get_aspect_ratio(&self) -> f64 {
+        self.film_width / self.film_height
+    }
+
+    fn get_frame(&self) -> &CameraFrame {
+        &self.frame
+    }
+
+    fn with_shutter(&self, t0: f64, t1: f64) -> CameraObject {
+        let mut c = self.clone();
+        c.frame.set_shutter(t0, t1);
+        make_camera(c)
+    }
+}
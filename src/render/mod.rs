@@ -1,12 +1,18 @@
-pub use camera::Camera;
+pub use camera::{Camera, CameraObject};
 pub use filter::GammaFilter;
 pub use multi_renderer::MultiRenderer;
 
 use crate::utils::Picture;
 
+pub mod aov;
 pub mod camera;
+pub mod camera_path;
+pub mod denoiser;
 pub mod filter;
+pub mod lens;
+pub mod reconstruction;
 pub mod skybox;
+pub mod stereo;
 
 pub mod multi_renderer;
 
@@ -3,23 +3,130 @@ use std::time;
 
 use num_traits::float::FloatCore;
 use rand::distributions::{Distribution, Uniform};
-use rand::thread_rng;
+use rand::{thread_rng, Rng};
 
-use crate::object::{Hittable, World};
-use crate::render::filter::Filter;
-use crate::render::{Camera, GammaFilter, Renderer};
+use crate::object::material::FilteredRay;
+use crate::object::{HitRecord, Hittable, World};
+use crate::render::aov::{Aov, AovBuffers};
+use crate::render::denoiser::{AtrousDenoiser, DenoiseParams};
+use crate::render::filter::{Filter, OutlierRejectionFilter};
+use crate::render::reconstruction::ReconstructionFilter;
+use crate::render::{CameraObject, GammaFilter, Renderer};
 use crate::utils::{Color, Picture, Ray};
 
+/// Cosine to the normal below which footprints stop stretching, bounding the blur of textures
+/// seen at grazing angles.
+const GRAZING_COSINE: f64 = 0.125;
+
 /// multi-threaded renderer
 pub struct MultiRenderer {
     width: usize,
     height: usize,
     sample_per_unit: usize,
     recursion_depth: usize,
-    camera: Arc<Option<Camera>>,
+    camera: Arc<Option<CameraObject>>,
     world: Arc<Option<World>>,
     use_gamma_correction: bool,
     thread_count: usize,
+    region: Option<RenderRegion>,
+    crop_output: CropOutput,
+    aovs: Vec<Aov>,
+    denoise: Option<DenoiseParams>,
+    pixel_filter: ReconstructionFilter,
+    russian_roulette: Option<usize>,
+    sample_clamp: Option<f32>,
+    indirect_clamp: Option<f32>,
+    outlier_rejection: Option<OutlierRejectionFilter>,
+}
+
+/// Statistics on the paths traced during a render.
+#[derive(Debug, Default, Copy, Clone)]
+pub struct PathStats {
+    pub paths: u64,
+    pub bounces: u64,
+    /// Paths terminated by russian roulette.
+    pub roulette_terminations: u64,
+    /// Paths cut off by the recursion depth limit.
+    pub truncations: u64,
+}
+
+impl PathStats {
+    pub fn merge(&mut self, other: &PathStats) {
+        self.paths += other.paths;
+        self.bounces += other.bounces;
+        self.roulette_terminations += other.roulette_terminations;
+        self.truncations += other.truncations;
+    }
+
+    pub fn average_length(&self) -> f64 {
+        if self.paths == 0 {
+            0.0
+        } else {
+            self.bounces as f64 / self.paths as f64
+        }
+    }
+
+    pub fn report(&self) {
+        println!(
+            "Paths = {}, average path length = {:.3}, terminated by russian roulette = {}, \
+             truncated by recursion depth = {}",
+            self.paths,
+            self.average_length(),
+            self.roulette_terminations,
+            self.truncations
+        );
+    }
+}
+
+/// Color used to mark pixels outside of the render region when `CropOutput::Marked` is used.
+pub const UNRENDERED_COLOR: Color = Color {
+    x: 1.0,
+    y: 0.0,
+    z: 1.0,
+};
+
+/// A rectangular region of the full image, in pixel coordinates.
+///
+/// `x0` & `y0` are inclusive while `x1` & `y1` are exclusive. Rows are counted from the top of
+/// the image, the same way as the output picture is stored.
+#[derive(Debug, Copy, Clone)]
+pub struct RenderRegion {
+    pub x0: usize,
+    pub y0: usize,
+    pub x1: usize,
+    pub y1: usize,
+}
+
+impl RenderRegion {
+    pub fn new(x0: usize, y0: usize, x1: usize, y1: usize) -> Self {
+        RenderRegion { x0, y0, x1, y1 }
+    }
+
+    /// The region covering the whole picture.
+    pub fn full(width: usize, height: usize) -> Self {
+        RenderRegion::new(0, 0, width, height)
+    }
+
+    pub fn width(&self) -> usize {
+        self.x1 - self.x0
+    }
+
+    pub fn height(&self) -> usize {
+        self.y1 - self.y0
+    }
+
+    pub fn contains(&self, x: usize, y: usize) -> bool {
+        x >= self.x0 && x < self.x1 && y >= self.y0 && y < self.y1
+    }
+}
+
+/// What the renderer outputs when only a region of the picture is rendered.
+#[derive(Debug, Copy, Clone)]
+pub enum CropOutput {
+    /// Output a picture of the size of the region.
+    Cropped,
+    /// Output a full-size picture, with pixels outside the region set to `UNRENDERED_COLOR`.
+    Marked,
 }
 
 pub enum PresetLevel {
@@ -41,6 +148,12 @@ impl PresetLevel {
     }
 }
 
+impl Default for MultiRenderer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl MultiRenderer {
     pub fn new() -> MultiRenderer {
         // use low preset for default
@@ -53,10 +166,19 @@ impl MultiRenderer {
             recursion_depth: 16,
             use_gamma_correction: true,
             thread_count: num_cpus::get(),
+            region: None,
+            crop_output: CropOutput::Cropped,
+            aovs: vec![],
+            denoise: None,
+            pixel_filter: ReconstructionFilter::default(),
+            russian_roulette: None,
+            sample_clamp: None,
+            indirect_clamp: None,
+            outlier_rejection: None,
         }
     }
 
-    pub fn set_camera(&mut self, camera: Camera) {
+    pub fn set_camera(&mut self, camera: CameraObject) {
         self.camera = Arc::new(Some(camera));
     }
 
@@ -76,6 +198,77 @@ impl MultiRenderer {
         self.recursion_depth = depth;
     }
 
+    /// Only render a sub-rectangle of the full picture. Use `None` to render everything.
+    pub fn set_render_region(&mut self, region: Option<RenderRegion>) {
+        self.region = region;
+    }
+
+    pub fn set_crop_output(&mut self, crop_output: CropOutput) {
+        self.crop_output = crop_output;
+    }
+
+    /// Set the filter used to reconstruct pixels from samples, a half-pixel box by default.
+    pub fn set_pixel_filter(&mut self, filter: ReconstructionFilter) {
+        self.pixel_filter = filter;
+    }
+
+    /// Terminate paths randomly by their throughput once they are `min_depth` bounces deep,
+    /// or never with `None`. The result stays unbiased, while dark paths end early and the
+    /// recursion depth can be raised affordably.
+    pub fn set_russian_roulette(&mut self, min_depth: Option<usize>) {
+        self.russian_roulette = min_depth;
+    }
+
+    /// Clamp the radiance of every camera sample to `max`. This biases the picture, so it is
+    /// off(`None`) by default.
+    pub fn set_sample_clamp(&mut self, max: Option<f32>) {
+        self.sample_clamp = max;
+    }
+
+    /// Clamp the radiance gathered after the first bounce to `max`, leaving directly visible
+    /// emitters & the sky box untouched. Off(`None`) by default.
+    pub fn set_indirect_clamp(&mut self, max: Option<f32>) {
+        self.indirect_clamp = max;
+    }
+
+    /// Replace isolated super-bright pixels with the average of their neighborhood, before
+    /// denoising. Off(`None`) by default.
+    pub fn set_outlier_rejection(&mut self, filter: Option<OutlierRejectionFilter>) {
+        self.outlier_rejection = filter;
+    }
+
+    /// Set the AOVs to render alongside the picture by `render_with_aovs`.
+    pub fn set_aovs(&mut self, aovs: &[Aov]) {
+        self.aovs = aovs.to_vec();
+    }
+
+    /// Gamma correct the output, on by default. Turn it off to get the linear picture.
+    pub fn set_gamma_correction(&mut self, enabled: bool) {
+        self.use_gamma_correction = enabled;
+    }
+
+    /// Denoise the linear picture with AOV-guided filtering, or turn it off with `None`.
+    pub fn set_denoise(&mut self, params: Option<DenoiseParams>) {
+        self.denoise = params;
+    }
+
+    /// AOVs that have to be rendered: the requested ones plus the features of the denoiser.
+    fn get_render_aovs(&self) -> Vec<Aov> {
+        let mut aovs = self.aovs.clone();
+        if self.denoise.is_some() {
+            for a in DenoiseParams::FEATURES.iter() {
+                if !aovs.contains(a) {
+                    aovs.push(*a);
+                }
+            }
+        }
+        aovs
+    }
+
+    pub fn get_size(&self) -> (usize, usize) {
+        (self.width, self.height)
+    }
+
     pub fn set_render_preset(&mut self, preset: PresetLevel) {
         let aspect = Option::as_ref(&self.camera).unwrap().get_aspect_ratio();
         match preset {
@@ -99,58 +292,206 @@ impl MultiRenderer {
         self.width = (self.height as f64 * aspect) as usize;
     }
 
-    // essentially the same as DefaultRenderer here
-    fn ray_color(world: &World, r: Ray, depth: usize) -> Color {
-        // don't do tail-recursion :)
-        // calculate
-        let mut r = r;
-        let mut coeff = Color::one();
-        let mut ret = Color::zero();
-        for _i in 0..depth {
-            if let Some(h) = world.hit(&r, 0.001, f64::infinity()) {
-                // emission material
-                ret += coeff * h.mat.emit(h.u, h.v, h.p);
-                if let Some(f) = h.mat.scatter(&r, &h) {
-                    // scattering material
-                    coeff *= f.attenuation;
-                    r = f.scattered;
+    /// Get the render region, validated against the current picture size.
+    fn get_region(&self) -> Result<RenderRegion, &'static str> {
+        match self.region {
+            None => Ok(RenderRegion::full(self.width, self.height)),
+            Some(r) => {
+                if r.x0 >= r.x1 || r.y0 >= r.y1 {
+                    Err("Render region is empty.")
+                } else if r.x1 > self.width || r.y1 > self.height {
+                    Err("Render region exceeds the picture size.")
                 } else {
-                    // no more scattering, return value :)
-                    return ret;
+                    Ok(r)
                 }
-            } else {
-                // sky box
-                return ret + coeff * world.get_skybox().get_color(&r);
             }
         }
-        Color::zero()
     }
-}
 
-impl Renderer for MultiRenderer {
-    fn render(&self) -> Result<Picture, &'static str> {
+    /// Trace a single pixel with verbose logging of every bounce to stdout.
+    ///
+    /// `x` & `y` are pixel coordinates in the full picture, with `y` counted from the top.
+    /// Returns the averaged color of the pixel before gamma correction.
+    pub fn debug_pixel(&self, x: usize, y: usize, samples: usize) -> Result<Color, &'static str> {
+        let world = Option::as_ref(&self.world).ok_or("World not set.")?;
+        let cam = Option::as_ref(&self.camera).ok_or("Camera not set.")?;
+        if x >= self.width || y >= self.height {
+            return Err("Debug pixel is out of the picture.");
+        }
+        if samples == 0 {
+            return Err("Debug pixel needs at least one sample.");
+        }
+        println!(
+            "Debugging pixel ({}, {}) of {} * {}, sample = {}, recursion depth = {}",
+            x, y, self.width, self.height, samples, self.recursion_depth
+        );
+        let mut rng = thread_rng();
+        let d1 = Uniform::from(0.0..(1.0 / self.height as f64));
+        let d2 = Uniform::from(0.0..(1.0 / self.width as f64));
+        let bv = (self.height - y - 1) as f64 / self.height as f64;
+        let bu = x as f64 / self.width as f64;
+        let mut c = Color::zero();
+        let mut traced = 0;
+        let mut stats = PathStats::default();
+        let cone = cam.pixel_cone(self.height);
+        for k in 0..samples {
+            let u = bu + d2.sample(&mut rng);
+            let v = bv + d1.sample(&mut rng);
+            let r = match cam.get_ray(u, v) {
+                Some(r) => r,
+                None => {
+                    println!("Sample {}: u={} v={} is outside the camera image", k, u, v);
+                    continue;
+                }
+            };
+            println!(
+                "Sample {}: u={} v={} origin={} dir={}",
+                k, u, v, r.orig, r.dir
+            );
+            let sc = self.sample_color(world, r, cone, &mut stats, true);
+            println!("Sample {} color = {}", k, sc);
+            c += sc;
+            traced += 1;
+        }
+        // average over the samples the camera covers, the pixel stays black without any
+        if traced > 0 {
+            c /= traced as f32;
+        }
+        println!("Pixel ({}, {}) color = {}", x, y, c);
+        stats.report();
+        Ok(c)
+    }
+
+    fn log_bounce(i: usize, h: &HitRecord, emitted: Color) {
+        println!("  bounce {}: hit {}", i, h);
+        println!(
+            "  bounce {}: material = {}, emitted = {}",
+            i,
+            h.mat.get_type(),
+            emitted
+        );
+    }
+
+    /// Add a sample at film position (`fx`, `fy`) to all pixels of the region within the radius
+    /// of the reconstruction filter.
+    #[inline(always)]
+    fn splat(
+        &self,
+        buffer: &mut Picture,
+        weights: &mut [f32],
+        region: &RenderRegion,
+        fx: f64,
+        fy: f64,
+        c: Color,
+    ) {
+        let radius = self.pixel_filter.radius;
+        // pixel centers are at half-integer positions
+        let x0 = ((fx - 0.5 - radius).ceil() as isize).max(region.x0 as isize);
+        let y0 = ((fy - 0.5 - radius).ceil() as isize).max(region.y0 as isize);
+        let x1 = ((fx - 0.5 + radius).floor() as isize).min(region.x1 as isize - 1);
+        let y1 = ((fy - 0.5 + radius).floor() as isize).min(region.y1 as isize - 1);
+        for y in y0..=y1 {
+            for x in x0..=x1 {
+                let w = self
+                    .pixel_filter
+                    .weight(fx - x as f64 - 0.5, fy - y as f64 - 0.5);
+                let idx = (y as usize - region.y0) * region.width() + (x as usize - region.x0);
+                buffer.data[idx] += c * w;
+                weights[idx] += w;
+            }
+        }
+    }
+
+    /// Place a cropped picture back into a full-size one, filling the rest with `fill`.
+    fn uncrop(&self, p: &Picture, region: &RenderRegion, fill: Color) -> Picture {
+        let mut full = Picture::new(self.width, self.height);
+        for (i, row) in full.data.chunks_mut(self.width).enumerate() {
+            for (j, c) in row.iter_mut().enumerate() {
+                *c = if region.contains(j, i) {
+                    p.at(j - region.x0, i - region.y0)
+                } else {
+                    fill
+                };
+            }
+        }
+        full
+    }
+
+    /// Render the scene through each of the cameras in turn, e.g. the eyes of a stereo rig.
+    ///
+    /// All views share the world & its BVH, and are rendered at the current picture size.
+    /// The camera set by `set_camera` is kept afterwards.
+    pub fn render_views(
+        &mut self,
+        cameras: &[CameraObject],
+    ) -> Result<Vec<(Picture, AovBuffers)>, &'static str> {
+        let camera = self.camera.clone();
+        let mut views = Vec::with_capacity(cameras.len());
+        for (i, c) in cameras.iter().enumerate() {
+            println!("Rendering view {} of {}...", i + 1, cameras.len());
+            self.camera = Arc::new(Some(c.clone()));
+            let result = self.render_with_aovs();
+            match result {
+                Ok(v) => views.push(v),
+                Err(e) => {
+                    self.camera = camera;
+                    return Err(e);
+                }
+            }
+        }
+        self.camera = camera;
+        Ok(views)
+    }
+
+    /// Render the picture together with the AOVs set by `set_aovs`.
+    pub fn render_with_aovs(&self) -> Result<(Picture, AovBuffers), &'static str> {
         if self.world.is_none() {
             return Err("World not set.");
         }
         if self.camera.is_none() {
             return Err("Camera not set.");
         }
+        let region = self.get_region()?;
+        let aovs = self.get_render_aovs();
+        let aovs = &aovs;
         println!(
             "Configuration: Picture size = {} * {}, sample = {}, recursion depth = {}",
             self.width, self.height, self.sample_per_unit, self.recursion_depth
         );
+        if self.region.is_some() {
+            println!(
+                "Render region = ({}, {}) ~ ({}, {})",
+                region.x0, region.y0, region.x1, region.y1
+            );
+        }
+        println!(
+            "Pixel filter = {:?}, radius = {}",
+            self.pixel_filter.filter, self.pixel_filter.radius
+        );
+        if let Some(d) = self.russian_roulette {
+            println!("Russian roulette after {} bounces", d);
+        }
+        if self.sample_clamp.is_some() || self.indirect_clamp.is_some() {
+            println!(
+                "Radiance clamp = {:?}, indirect radiance clamp = {:?}",
+                self.sample_clamp, self.indirect_clamp
+            );
+        }
+        if !aovs.is_empty() {
+            let names: Vec<&str> = aovs.iter().map(|a| a.name()).collect();
+            println!("AOVs = {}", names.join(", "));
+        }
 
         let t = time::SystemTime::now();
 
         // use scoped thread here
-        let result = crossbeam::thread::scope(|s| -> Picture {
+        let result = crossbeam::thread::scope(|s| -> (Picture, Vec<Picture>, PathStats) {
             println!(
                 "Initializing threads... Thread count = {}",
                 self.thread_count
             );
-            let _p = Picture::new(self.width, self.height);
-            let sample_per_thread =
-                (self.sample_per_unit + self.thread_count - 1) / self.thread_count;
+            let sample_per_thread = self.sample_per_unit.div_ceil(self.thread_count);
+            let new_buffer = || Picture::new(region.width(), region.height());
             let rx = {
                 let (tx, rx) = mpsc::channel();
                 // divide the job by sample count per pixel
@@ -161,47 +502,128 @@ impl Renderer for MultiRenderer {
                         let mut rng = thread_rng();
                         let world = Option::as_ref(&self.world).unwrap();
                         let cam = Option::as_ref(&self.camera).unwrap();
-                        let d1 = Uniform::from(0.0..(1.0 / self.height as f64));
-                        let d2 = Uniform::from(0.0..(1.0 / self.width as f64));
-                        let mut buffer = Box::new(Picture::new(self.width, self.height));
-                        for i in 0..self.height {
-                            for j in 0..self.width {
-                                let mut c: Color = Color::default();
-                                let bv = (self.height - i - 1) as f64 / self.height as f64;
-                                let bu = j as f64 / self.width as f64;
-                                for _k in 0..sample_per_thread {
-                                    let v = bv + d1.sample(&mut rng);
-                                    let u = bu + d2.sample(&mut rng);
-                                    c += MultiRenderer::ray_color(
-                                        world,
-                                        cam.get_ray(u, v),
-                                        self.recursion_depth,
-                                    );
+                        let cone = cam.pixel_cone(self.height);
+                        let d = Uniform::from(0.0..1.0);
+                        let mut buffer = Box::new(new_buffer());
+                        let mut weights = vec![0.0_f32; region.width() * region.height()];
+                        let mut stats = PathStats::default();
+                        let mut aov_buffers: Vec<Picture> =
+                            aovs.iter().map(|_| new_buffer()).collect();
+                        for i in region.y0..region.y1 {
+                            for j in region.x0..region.x1 {
+                                let idx = (i - region.y0) * region.width() + (j - region.x0);
+                                for k in 0..sample_per_thread {
+                                    // sample position on the film, in pixels from the top left
+                                    let fx = j as f64 + d.sample(&mut rng);
+                                    let fy = i as f64 + d.sample(&mut rng);
+                                    let u = fx / self.width as f64;
+                                    let v = (self.height as f64 - fy) / self.height as f64;
+                                    let r = match cam.get_ray(u, v) {
+                                        Some(r) => r,
+                                        None => {
+                                            // outside the camera image, keep the pixel black
+                                            let c = Color::zero();
+                                            self.splat(
+                                                &mut buffer,
+                                                &mut weights,
+                                                &region,
+                                                fx,
+                                                fy,
+                                                c,
+                                            );
+                                            continue;
+                                        }
+                                    };
+                                    if !aovs.is_empty() {
+                                        let h =
+                                            world.hit(&r, 0.001, f64::infinity()).map(|mut h| {
+                                                let width = cone.0 + cone.1 * h.t * r.dir.length();
+                                                h.footprint =
+                                                    MultiRenderer::footprint(&r, &h, width);
+                                                h
+                                            });
+                                        for (aov, b) in aovs.iter().zip(&mut aov_buffers) {
+                                            // categorical AOVs only come from a single sample
+                                            if !aov.is_categorical() || (thread_id == 0 && k == 0) {
+                                                b.data[idx] += aov.evaluate(&r, h.as_ref());
+                                            }
+                                        }
+                                    }
+                                    let c = self.sample_color(world, r, cone, &mut stats, false);
+                                    self.splat(&mut buffer, &mut weights, &region, fx, fy, c);
                                 }
-                                buffer.data[i * self.width + j] = c;
                             }
                         }
-                        txc.send(buffer).expect("Buffer exchanging failed.");
+                        txc.send((buffer, weights, aov_buffers, stats))
+                            .expect("Buffer exchanging failed.");
                         println!("Thread {} exit.", thread_id);
                     });
                 }
                 rx
             }; // tx at this point should be invalidated
-            let mut buffer = Picture::new(self.width, self.height);
-            for buf in rx {
+            let mut buffer = new_buffer();
+            let mut weights = vec![0.0_f32; region.width() * region.height()];
+            let mut stats = PathStats::default();
+            let mut aov_buffers: Vec<Picture> = aovs.iter().map(|_| new_buffer()).collect();
+            for (buf, w, aov_bufs, st) in rx {
+                stats.merge(&st);
                 for (u, v) in buffer.data.iter_mut().zip(buf.data.iter()) {
                     *u += *v;
                 }
+                for (u, v) in weights.iter_mut().zip(w.iter()) {
+                    *u += *v;
+                }
+                for (a, b) in aov_buffers.iter_mut().zip(aov_bufs.iter()) {
+                    for (u, v) in a.data.iter_mut().zip(b.data.iter()) {
+                        *u += *v;
+                    }
+                }
             }
-            for u in &mut buffer.data {
-                *u /= self.sample_per_unit as f32;
+            for (u, w) in buffer.data.iter_mut().zip(weights.iter()) {
+                // filters with negative lobes may leave a pixel with no weight at all,
+                // or ring below zero next to bright samples
+                *u = if w.abs() > 1e-6 {
+                    (*u / *w).apply(|x| x.max(0.0))
+                } else {
+                    Color::zero()
+                };
+            }
+            let sample_count = (sample_per_thread * self.thread_count) as f32;
+            for (aov, a) in aovs.iter().zip(aov_buffers.iter_mut()) {
+                if !aov.is_categorical() {
+                    for u in &mut a.data {
+                        *u /= sample_count;
+                    }
+                }
             }
-            buffer
+            (buffer, aov_buffers, stats)
         });
         if result.is_err() {
             return Err("Error occurred during multi-threaded rendering.");
         }
-        let mut buffer = result.unwrap();
+        let (mut buffer, aov_buffers, stats) = result.unwrap();
+        stats.report();
+        let mut aov_buffers = AovBuffers {
+            buffers: aovs.iter().copied().zip(aov_buffers).collect(),
+        };
+        if let Some(f) = &self.outlier_rejection {
+            println!("Rejecting outliers...");
+            f.filter(&mut buffer);
+        }
+        if let Some(params) = self.denoise {
+            println!("Denoising...");
+            let denoiser = AtrousDenoiser::from_aovs(params, &aov_buffers)
+                .ok_or("Denoiser features are not rendered.")?;
+            denoiser.filter(&mut buffer);
+        }
+        // only return the AOVs that were asked for
+        aov_buffers.buffers.retain(|(a, _)| self.aovs.contains(a));
+        if let CropOutput::Marked = self.crop_output {
+            buffer = self.uncrop(&buffer, &region, UNRENDERED_COLOR);
+            for (_, p) in aov_buffers.buffers.iter_mut() {
+                *p = self.uncrop(p, &region, Color::zero());
+            }
+        }
         if self.use_gamma_correction {
             let filter = GammaFilter { gamma: 2.0 };
             filter.filter(&mut buffer);
@@ -210,6 +632,173 @@ impl Renderer for MultiRenderer {
             "Done, time elapsed = {:?}",
             time::SystemTime::now().duration_since(t).unwrap()
         );
-        Ok(buffer)
+        Ok((buffer, aov_buffers))
+    }
+
+    /// Send half of the rays scattered off diffuse materials towards the lights of the world.
+    ///
+    /// The attenuation of the material assumes the direction was sampled by its own pdf, so it
+    /// is weighted by the ratio of that pdf to the pdf of the mixture.
+    fn sample_lights(world: &World, r: &Ray, h: &HitRecord, f: FilteredRay) -> FilteredRay {
+        if !world.has_lights() || h.mat.scattering_pdf(r, h, &f.scattered) <= 0.0 {
+            return f;
+        }
+        let mut scattered = f.scattered;
+        if thread_rng().gen::<bool>() {
+            if let Some(dir) = world.sample_light(h.p) {
+                scattered.dir = dir;
+            }
+        }
+        let material_pdf = h.mat.scattering_pdf(r, h, &scattered);
+        let pdf = 0.5 * material_pdf + 0.5 * world.light_pdf(h.p, scattered.dir);
+        let weight = if pdf > 0.0 { material_pdf / pdf } else { 0.0 };
+        FilteredRay {
+            attenuation: f.attenuation * weight as f32,
+            scattered,
+        }
+    }
+
+    /// Scale a color down so that none of its components exceeds `max`, keeping its hue.
+    #[inline(always)]
+    fn clamp_radiance(c: Color, max: f32) -> Color {
+        let m = c.x.max(c.y).max(c.z);
+        if m > max {
+            c * (max / m)
+        } else {
+            c
+        }
+    }
+
+    /// Footprint of hit `h` of ray `r` for a pixel cone `width` wide at the hit, stretched on
+    /// surfaces seen at an angle.
+    fn footprint(r: &Ray, h: &HitRecord, width: f64) -> f64 {
+        let cosine = h.normal.dot(r.dir.unit_vector()).abs();
+        width / cosine.max(GRAZING_COSINE)
+    }
+
+    /// Add the contribution of a single bounce, clamping it if it is indirect.
+    #[inline(always)]
+    fn add_contribution(&self, ret: &mut Color, bounce: usize, c: Color) {
+        *ret += match self.indirect_clamp {
+            Some(max) if bounce > 0 => MultiRenderer::clamp_radiance(c, max),
+            _ => c,
+        };
+    }
+
+    /// Trace a camera sample, applying the per-sample clamp.
+    #[inline(always)]
+    fn sample_color(
+        &self,
+        world: &World,
+        r: Ray,
+        cone: (f64, f64),
+        stats: &mut PathStats,
+        debug: bool,
+    ) -> Color {
+        let c = self.ray_color(world, r, cone, stats, debug);
+        match self.sample_clamp {
+            Some(max) => MultiRenderer::clamp_radiance(c, max),
+            None => c,
+        }
+    }
+
+    // essentially the same as DefaultRenderer here
+    //
+    // `cone` is the pixel cone of the camera, which keeps widening at the same rate along the
+    // whole path to give every hit a footprint for texture filtering.
+    fn ray_color(
+        &self,
+        world: &World,
+        r: Ray,
+        cone: (f64, f64),
+        stats: &mut PathStats,
+        debug: bool,
+    ) -> Color {
+        // don't do tail-recursion :)
+        // calculate
+        let depth = self.recursion_depth;
+        let mut r = r;
+        let mut rng = thread_rng();
+        let mut throughput = Color::one();
+        let mut ret = Color::zero();
+        let (mut width, spread) = cone;
+        stats.paths += 1;
+        for i in 0..depth {
+            stats.bounces += 1;
+            if let Some(mut h) = world.hit(&r, 0.001, f64::infinity()) {
+                width += spread * h.t * r.dir.length();
+                h.footprint = MultiRenderer::footprint(&r, &h, width);
+                // emission material
+                let emitted = h.mat.emit(&h);
+                self.add_contribution(&mut ret, i, throughput * emitted);
+                if debug {
+                    MultiRenderer::log_bounce(i, &h, emitted);
+                }
+                if let Some(f) = h.mat.scatter(&r, &h) {
+                    // scattering material
+                    let f = MultiRenderer::sample_lights(world, &r, &h, f);
+                    throughput *= f.attenuation;
+                    r = f.scattered;
+                    if debug {
+                        println!(
+                            "  bounce {}: attenuation = {}, throughput = {}, scattered dir = {}",
+                            i, f.attenuation, throughput, r.dir
+                        );
+                    }
+                } else {
+                    // no more scattering, return value :)
+                    if debug {
+                        println!("  bounce {}: absorbed, path terminated", i);
+                    }
+                    return ret;
+                }
+                if let Some(min_depth) = self.russian_roulette {
+                    if i + 1 >= min_depth {
+                        // survive with a probability following the throughput, and make up
+                        // for the terminated paths by boosting the survivors
+                        let p = throughput.x.max(throughput.y).max(throughput.z).min(0.95);
+                        if rng.gen::<f32>() >= p {
+                            stats.roulette_terminations += 1;
+                            if debug {
+                                println!(
+                                    "  bounce {}: terminated by russian roulette, p = {}",
+                                    i, p
+                                );
+                            }
+                            return ret;
+                        }
+                        throughput /= p;
+                        if debug {
+                            println!(
+                                "  bounce {}: survived russian roulette, p = {}, throughput = {}",
+                                i, p, throughput
+                            );
+                        }
+                    }
+                }
+            } else {
+                // sky box
+                let sky = world.get_skybox().get_color(&r);
+                if debug {
+                    println!("  bounce {}: missed, sky box color = {}", i, sky);
+                }
+                self.add_contribution(&mut ret, i, throughput * sky);
+                return ret;
+            }
+        }
+        stats.truncations += 1;
+        if debug {
+            println!(
+                "  recursion depth {} exceeded, path truncated, throughput = {}",
+                depth, throughput
+            );
+        }
+        ret
+    }
+}
+
+impl Renderer for MultiRenderer {
+    fn render(&self) -> Result<Picture, &'static str> {
+        self.render_with_aovs().map(|(p, _)| p)
     }
 }
@@ -0,0 +1,123 @@
+//! Reconstruction: pixel reconstruction filters used to splat samples onto the picture.
+//!
+//! Every camera sample contributes to all pixels whose centers lie within the filter radius,
+//! weighted by the filter, and each pixel is divided by the sum of the weights it received.
+//! A box filter with a radius of half a pixel gives the plain per-pixel average.
+
+use std::f64::consts::PI;
+
+#[derive(Debug, Copy, Clone)]
+pub enum PixelFilter {
+    Box,
+    Tent,
+    /// Gaussian with falloff `alpha`, shifted so that it reaches zero at the radius.
+    Gaussian {
+        alpha: f64,
+    },
+    /// Mitchell-Netravali cubic with parameters `b` & `c`, stretched to the radius.
+    Mitchell {
+        b: f64,
+        c: f64,
+    },
+    /// Sinc windowed by a wider sinc, `tau` being the number of lobes of the window.
+    Lanczos {
+        tau: f64,
+    },
+}
+
+impl PixelFilter {
+    /// Get a filter with its usual parameters by name.
+    pub fn from_name(name: &str) -> Option<PixelFilter> {
+        match name {
+            "box" => Some(PixelFilter::Box),
+            "tent" => Some(PixelFilter::Tent),
+            "gaussian" => Some(PixelFilter::Gaussian { alpha: 2.0 }),
+            "mitchell" => Some(PixelFilter::Mitchell {
+                b: 1.0 / 3.0,
+                c: 1.0 / 3.0,
+            }),
+            "lanczos" => Some(PixelFilter::Lanczos { tau: 3.0 }),
+            _ => None,
+        }
+    }
+
+    /// The radius the filter is usually used with, in pixels.
+    pub fn default_radius(&self) -> f64 {
+        match self {
+            PixelFilter::Box => 0.5,
+            PixelFilter::Tent => 1.0,
+            PixelFilter::Gaussian { .. } => 1.5,
+            PixelFilter::Mitchell { .. } => 2.0,
+            PixelFilter::Lanczos { .. } => 2.0,
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone)]
+pub struct ReconstructionFilter {
+    pub filter: PixelFilter,
+    /// Radius of the filter support in pixels, in both directions.
+    pub radius: f64,
+}
+
+impl Default for ReconstructionFilter {
+    fn default() -> Self {
+        ReconstructionFilter {
+            filter: PixelFilter::Box,
+            radius: 0.5,
+        }
+    }
+}
+
+impl ReconstructionFilter {
+    pub fn new(filter: PixelFilter, radius: f64) -> Self {
+        ReconstructionFilter { filter, radius }
+    }
+
+    /// Weight of a sample at offset (`dx`, `dy`) from a pixel center, in pixels.
+    #[inline(always)]
+    pub fn weight(&self, dx: f64, dy: f64) -> f32 {
+        (self.weight_1d(dx) * self.weight_1d(dy)) as f32
+    }
+
+    #[inline(always)]
+    fn weight_1d(&self, x: f64) -> f64 {
+        let r = self.radius;
+        let x = x.abs();
+        if x > r {
+            return 0.0;
+        }
+        match self.filter {
+            PixelFilter::Box => 1.0,
+            PixelFilter::Tent => r - x,
+            PixelFilter::Gaussian { alpha } => {
+                ((-alpha * x * x).exp() - (-alpha * r * r).exp()).max(0.0)
+            }
+            PixelFilter::Mitchell { b, c } => Self::mitchell(2.0 * x / r, b, c),
+            PixelFilter::Lanczos { tau } => Self::sinc(x) * Self::sinc(x / tau),
+        }
+    }
+
+    /// Mitchell-Netravali cubic, defined on [0, 2].
+    fn mitchell(x: f64, b: f64, c: f64) -> f64 {
+        let v = if x > 1.0 {
+            (-b - 6.0 * c) * x * x * x
+                + (6.0 * b + 30.0 * c) * x * x
+                + (-12.0 * b - 48.0 * c) * x
+                + (8.0 * b + 24.0 * c)
+        } else {
+            (12.0 - 9.0 * b - 6.0 * c) * x * x * x
+                + (-18.0 + 12.0 * b + 6.0 * c) * x * x
+                + (6.0 - 2.0 * b)
+        };
+        v / 6.0
+    }
+
+    fn sinc(x: f64) -> f64 {
+        if x < 1e-5 {
+            1.0
+        } else {
+            (PI * x).sin() / (PI * x)
+        }
+    }
+}
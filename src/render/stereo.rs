@@ -0,0 +1,120 @@
+//! Stereo: a pair of eye cameras, & the layouts packing both views into one picture.
+//!
+//! The eyes look in parallel directions and use off-axis projections, their film windows
+//! overlapping exactly on the convergence plane. This avoids the vertical parallax of
+//! toed-in cameras.
+
+use crate::render::camera::{make_camera, Camera, CameraObject, PerspectiveCamera};
+use crate::utils::{Picture, Vec3};
+
+pub struct StereoRig {
+    pub left: PerspectiveCamera,
+    pub right: PerspectiveCamera,
+}
+
+impl StereoRig {
+    /// Create a rig centered at `origin`, see `PerspectiveCamera::look_from`.
+    ///
+    /// * `interocular`: Distance between the eyes.
+    /// * `convergence`: Distance of the plane where objects appear at the depth of the screen,
+    ///   also used as the focus distance.
+    #[allow(clippy::too_many_arguments)]
+    pub fn look_from(
+        origin: Vec3<f64>,
+        look_at: Vec3<f64>,
+        v_up: Vec3<f64>,
+        vfov: f64,
+        aspect: f64,
+        aperture: f64,
+        interocular: f64,
+        convergence: f64,
+        t0: f64,
+        t1: f64,
+    ) -> Self {
+        let center = PerspectiveCamera::look_from(
+            origin,
+            look_at,
+            v_up,
+            vfov,
+            aspect,
+            aperture,
+            convergence,
+            t0,
+            t1,
+        );
+        Self::around(&center, interocular, convergence)
+    }
+
+    /// Create a rig with the eyes on both sides of `center`, keeping its focus distance.
+    pub fn around(center: &PerspectiveCamera, interocular: f64, convergence: f64) -> Self {
+        assert!(
+            convergence > 0.0,
+            "Convergence distance should be positive."
+        );
+        let eye = |side: f64| {
+            let mut c = center.clone();
+            c.translate(center.get_frame().u * (side * interocular / 2.0));
+            // the window is shifted back towards the center line, scaled to the focus plane
+            let shift = -side * interocular / 2.0 * center.get_focus_distance() / convergence;
+            c.shift_window(shift, 0.0);
+            c
+        };
+        StereoRig {
+            left: eye(-1.0),
+            right: eye(1.0),
+        }
+    }
+
+    /// Get the cameras of the left & right eyes, in this order.
+    pub fn views(&self) -> Vec<CameraObject> {
+        vec![
+            make_camera(self.left.clone()),
+            make_camera(self.right.clone()),
+        ]
+    }
+}
+
+#[derive(Debug, Copy, Clone)]
+pub enum StereoLayout {
+    /// Left eye on the left half, right eye on the right half.
+    SideBySide,
+    /// Left eye on the top half, right eye on the bottom half.
+    TopBottom,
+}
+
+impl StereoLayout {
+    pub fn from_name(name: &str) -> Option<StereoLayout> {
+        match name {
+            "side-by-side" => Some(StereoLayout::SideBySide),
+            "top-bottom" => Some(StereoLayout::TopBottom),
+            _ => None,
+        }
+    }
+
+    /// Pack the pictures of both eyes, which should be of the same size, into one.
+    pub fn combine(&self, left: &Picture, right: &Picture) -> Picture {
+        assert!(left.width == right.width && left.height == right.height);
+        let (w, h) = (left.width, left.height);
+        match self {
+            StereoLayout::SideBySide => {
+                let mut p = Picture::new(2 * w, h);
+                for y in 0..h {
+                    let row = y * 2 * w;
+                    p.data[row..row + w].copy_from_slice(&left.data[y * w..(y + 1) * w]);
+                    p.data[row + w..row + 2 * w].copy_from_slice(&right.data[y * w..(y + 1) * w]);
+                }
+                p
+            }
+            StereoLayout::TopBottom => {
+                let mut data = left.data.clone();
+                data.extend_from_slice(&right.data);
+                Picture {
+                    width: w,
+                    height: 2 * h,
+                    data,
+                    alpha: None,
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,126 @@
+//! Keyframe: values animated over time by interpolating between keyframes.
+
+use std::ops::{Add, Mul, Sub};
+
+/// How a value moves from a keyframe to the next one.
+#[derive(Debug, Copy, Clone)]
+pub enum Interpolation {
+    Linear,
+    /// Smooth curve through the neighbouring keyframes as well.
+    CatmullRom,
+    /// Accelerate out of the keyframe & decelerate into the next one.
+    Ease,
+}
+
+impl Interpolation {
+    pub fn from_name(name: &str) -> Option<Interpolation> {
+        match name {
+            "linear" => Some(Interpolation::Linear),
+            "catmull-rom" => Some(Interpolation::CatmullRom),
+            "ease" => Some(Interpolation::Ease),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone)]
+pub struct Keyframe<T> {
+    pub time: f64,
+    pub value: T,
+    /// Interpolation towards the next keyframe.
+    pub interpolation: Interpolation,
+}
+
+/// A value animated by keyframes, held constant before the first & after the last one.
+#[derive(Debug, Clone)]
+pub struct Keyframes<T> {
+    keys: Vec<Keyframe<T>>,
+}
+
+impl<T> Keyframes<T>
+where
+    T: Copy + Add<Output = T> + Sub<Output = T> + Mul<f64, Output = T>,
+{
+    /// Create an animation starting with `value` at `time`.
+    pub fn new(time: f64, value: T) -> Self {
+        Keyframes {
+            keys: vec![Keyframe {
+                time,
+                value,
+                interpolation: Interpolation::Linear,
+            }],
+        }
+    }
+
+    /// Create a value that never changes.
+    pub fn constant(value: T) -> Self {
+        Self::new(0.0, value)
+    }
+
+    /// Add a keyframe, replacing any keyframe at the same time. `interpolation` is used from
+    /// this keyframe to the next one.
+    pub fn add(mut self, time: f64, value: T, interpolation: Interpolation) -> Self {
+        let key = Keyframe {
+            time,
+            value,
+            interpolation,
+        };
+        match self.keys.iter().position(|k| k.time >= time) {
+            Some(i) if self.keys[i].time == time => self.keys[i] = key,
+            Some(i) => self.keys.insert(i, key),
+            None => self.keys.push(key),
+        }
+        self
+    }
+
+    pub fn keys(&self) -> &[Keyframe<T>] {
+        &self.keys
+    }
+
+    /// Get the times of the first & the last keyframes.
+    pub fn time_range(&self) -> (f64, f64) {
+        (self.keys[0].time, self.keys[self.keys.len() - 1].time)
+    }
+
+    /// Evaluate the animated value at `time`.
+    pub fn at(&self, time: f64) -> T {
+        let n = self.keys.len();
+        // index of the first keyframe after the time
+        let i = self.keys.partition_point(|k| k.time <= time);
+        if i == 0 {
+            return self.keys[0].value;
+        }
+        if i == n {
+            return self.keys[n - 1].value;
+        }
+        let (k0, k1) = (&self.keys[i - 1], &self.keys[i]);
+        let s = (time - k0.time) / (k1.time - k0.time);
+        match k0.interpolation {
+            Interpolation::Linear => k0.value + (k1.value - k0.value) * s,
+            Interpolation::Ease => {
+                let s = s * s * (3.0 - 2.0 * s);
+                k0.value + (k1.value - k0.value) * s
+            }
+            Interpolation::CatmullRom => {
+                // the curve is continued straight at the ends
+                let p0 = if i >= 2 {
+                    self.keys[i - 2].value
+                } else {
+                    k0.value - (k1.value - k0.value)
+                };
+                let p3 = if i + 1 < n {
+                    self.keys[i + 1].value
+                } else {
+                    k1.value + (k1.value - k0.value)
+                };
+                let (p1, p2) = (k0.value, k1.value);
+                let (s2, s3) = (s * s, s * s * s);
+                (p1 * 2.0
+                    + (p2 - p0) * s
+                    + (p0 * 2.0 - p1 * 5.0 + p2 * 4.0 - p3) * s2
+                    + (p1 * 3.0 - p0 - p2 * 3.0 + p3) * s3)
+                    * 0.5
+            }
+        }
+    }
+}
@@ -1,17 +1,23 @@
 pub use ray::Ray;
 pub use vec::Vec3;
 
+pub mod keyframe;
 pub mod perlin;
+pub mod poly;
 pub mod ray;
+pub mod transform;
 pub mod vec;
 
 /// Color
 pub type Color = Vec3<f32>;
 
+/// A picture of `width` x `height` pixels in rows from the top, with an optional alpha channel.
 pub struct Picture {
     pub width: usize,
     pub height: usize,
     pub data: Vec<Color>,
+    /// Opacity of every pixel in [0, 1], or None for a fully opaque picture.
+    pub alpha: Option<Vec<f32>>,
 }
 
 impl Picture {
@@ -20,6 +26,7 @@ impl Picture {
             width,
             height,
             data: vec![Color::zero(); width * height],
+            alpha: None,
         }
     }
 
@@ -27,4 +34,12 @@ impl Picture {
     pub fn at(&self, x: usize, y: usize) -> Color {
         self.data[y * self.width + x]
     }
+
+    #[inline]
+    pub fn alpha_at(&self, x: usize, y: usize) -> f32 {
+        match &self.alpha {
+            Some(alpha) => alpha[y * self.width + x],
+            None => 1.0,
+        }
+    }
 }
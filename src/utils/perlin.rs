@@ -16,6 +16,12 @@ pub struct Perlin {
     random_vector: Vec<Vec3<f64>>,
 }
 
+impl Default for Perlin {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl Perlin {
     pub fn new() -> Perlin {
         let mut rng = thread_rng();
@@ -44,6 +50,7 @@ impl Perlin {
         self.random_float[(self.perm_x[x] ^ self.perm_y[y] ^ self.perm_z[z]) as usize]
     }
 
+    #[allow(clippy::needless_range_loop)]
     pub fn smoothed_noise(&self, p: Vec3<f64>, frequency: f64) -> f32 {
         let p = p * frequency;
         // Hermitian smoothing
@@ -65,6 +72,7 @@ impl Perlin {
         Perlin::trilinear_interpolate(&c, u, v, w)
     }
 
+    #[allow(clippy::needless_range_loop)]
     pub fn smoothed_shifted_noise(&self, p: Vec3<f64>, frequency: f64) -> f32 {
         let p = p * frequency;
         let Vec3{x: u, y: v, z: w} = p.apply(|x| x - x.floor());
@@ -83,6 +91,7 @@ impl Perlin {
         0.5 * (1.0 + Perlin::perlin_interpolate(&c, u, v, w))
     }
 
+    #[allow(clippy::needless_range_loop)]
     #[inline(always)]
     fn trilinear_interpolate(c: &[[[f32; 2]; 2]; 2], u: f64, v: f64, w: f64) -> f32 {
         let mut ret = 0.0;
@@ -101,6 +110,7 @@ impl Perlin {
         ret as f32
     }
 
+    #[allow(clippy::needless_range_loop)]
     #[inline(always)]
     fn perlin_interpolate(c: &[[[Vec3<f64>; 2]; 2]; 2], u: f64, v: f64, w: f64) -> f32 {
         let Vec3{x: hu, y: hv, z: hw} = Vec3::new(u, v, w).apply(|x| x * x * (3.0 - 2.0 * x));
@@ -0,0 +1,114 @@
+//! Real roots of low degree polynomials, for intersecting rays with curved surfaces.
+
+/// Real roots of `a x^2 + b x + c` in ascending order, with a single root repeated.
+///
+/// It returns an array rather than a list, so it doesn't allocate in the intersection tests.
+pub fn solve_quadratic(a: f64, b: f64, c: f64) -> Option<[f64; 2]> {
+    if a.abs() < 1e-12 {
+        return if b.abs() < 1e-12 {
+            None
+        } else {
+            Some([-c / b; 2])
+        };
+    }
+    let discriminant = b * b - 4.0 * a * c;
+    if discriminant < 0.0 {
+        return None;
+    }
+    // avoid cancellation by computing the larger root first
+    let q = -0.5 * (b + b.signum() * discriminant.sqrt());
+    if q == 0.0 {
+        return Some([0.0; 2]);
+    }
+    let (x0, x1) = (q / a, c / q);
+    Some([x0.min(x1), x0.max(x1)])
+}
+
+/// The largest real root of `x^3 + a x^2 + b x + c`.
+pub fn largest_cubic_root(a: f64, b: f64, c: f64) -> f64 {
+    // depressed cubic t^3 + p t + q with x = t - a / 3
+    let p = b - a * a / 3.0;
+    let q = 2.0 * a * a * a / 27.0 - a * b / 3.0 + c;
+    let discriminant = q * q / 4.0 + p * p * p / 27.0;
+    let t = if discriminant >= 0.0 {
+        let s = discriminant.sqrt();
+        (-q / 2.0 + s).cbrt() + (-q / 2.0 - s).cbrt()
+    } else {
+        // three real roots, trigonometric solution
+        let r = 2.0 * (-p / 3.0).sqrt();
+        let phi = ((3.0 * q / (2.0 * p)) * (-3.0 / p).sqrt())
+            .clamp(-1.0, 1.0)
+            .acos();
+        r * (phi / 3.0).cos()
+    };
+    let x = t - a / 3.0;
+    // the closed form loses precision, so polish with Newton's method
+    polish(&[1.0, a, b, c], x)
+}
+
+/// Real roots of `c[0] x^4 + c[1] x^3 + c[2] x^2 + c[3] x + c[4]` with Ferrari's method, in
+/// ascending order.
+pub fn solve_quartic(c: [f64; 5]) -> Vec<f64> {
+    if c[0].abs() < 1e-12 {
+        return vec![];
+    }
+    let (a, b, cc, d) = (c[1] / c[0], c[2] / c[0], c[3] / c[0], c[4] / c[0]);
+    // depressed quartic y^4 + p y^2 + q y + r with x = y - a / 4
+    let a2 = a * a;
+    let p = b - 3.0 * a2 / 8.0;
+    let q = cc - a * b / 2.0 + a2 * a / 8.0;
+    let r = d - a * cc / 4.0 + a2 * b / 16.0 - 3.0 * a2 * a2 / 256.0;
+
+    let mut ys = vec![];
+    if q.abs() < 1e-12 {
+        // biquadratic
+        for z in solve_quadratic(1.0, p, r).into_iter().flatten() {
+            if z >= 0.0 {
+                ys.push(z.sqrt());
+                ys.push(-z.sqrt());
+            }
+        }
+    } else {
+        // split into two quadratics with a positive root of the resolvent cubic
+        let m = largest_cubic_root(p, p * p / 4.0 - r, -q * q / 8.0);
+        if m <= 0.0 {
+            return vec![];
+        }
+        let s = (2.0 * m).sqrt();
+        ys.extend(
+            solve_quadratic(1.0, -s, p / 2.0 + m + q / (2.0 * s))
+                .into_iter()
+                .flatten(),
+        );
+        ys.extend(
+            solve_quadratic(1.0, s, p / 2.0 + m - q / (2.0 * s))
+                .into_iter()
+                .flatten(),
+        );
+    }
+    let coefficients = [1.0, a, b, cc, d];
+    let mut xs: Vec<f64> = ys
+        .into_iter()
+        .map(|y| polish(&coefficients, y - a / 4.0))
+        .collect();
+    xs.sort_by(|x, y| x.total_cmp(y));
+    xs
+}
+
+/// Refine a root of the polynomial with `coefficients`, highest degree first, with a few
+/// Newton iterations.
+fn polish(coefficients: &[f64], x: f64) -> f64 {
+    let mut x = x;
+    for _ in 0..2 {
+        let (mut f, mut df) = (0.0, 0.0);
+        for c in coefficients {
+            df = df * x + f;
+            f = f * x + c;
+        }
+        if df.abs() < 1e-12 {
+            break;
+        }
+        x -= f / df;
+    }
+    x
+}
@@ -0,0 +1,261 @@
+//! Transform: 3x3 matrices & similarity transforms for placing objects in the world.
+
+use std::ops::Mul;
+
+use crate::utils::Vec3;
+
+/// Row-major 3x3 matrix.
+#[derive(Debug, Copy, Clone)]
+pub struct Mat3 {
+    pub rows: [Vec3<f64>; 3],
+}
+
+impl Mat3 {
+    pub fn from_rows(r0: Vec3<f64>, r1: Vec3<f64>, r2: Vec3<f64>) -> Self {
+        Mat3 { rows: [r0, r1, r2] }
+    }
+
+    pub fn identity() -> Self {
+        Mat3::from_rows(
+            Vec3::new(1.0, 0.0, 0.0),
+            Vec3::new(0.0, 1.0, 0.0),
+            Vec3::new(0.0, 0.0, 1.0),
+        )
+    }
+
+    /// Rotation around the x axis by `angle` in degrees, counter-clockwise looking down the axis.
+    pub fn rotation_x(angle: f64) -> Self {
+        let (s, c) = angle.to_radians().sin_cos();
+        Mat3::from_rows(
+            Vec3::new(1.0, 0.0, 0.0),
+            Vec3::new(0.0, c, -s),
+            Vec3::new(0.0, s, c),
+        )
+    }
+
+    /// Rotation around the y axis by `angle` in degrees, same as `RotateY`.
+    pub fn rotation_y(angle: f64) -> Self {
+        let (s, c) = angle.to_radians().sin_cos();
+        Mat3::from_rows(
+            Vec3::new(c, 0.0, s),
+            Vec3::new(0.0, 1.0, 0.0),
+            Vec3::new(-s, 0.0, c),
+        )
+    }
+
+    pub fn rotation_z(angle: f64) -> Self {
+        let (s, c) = angle.to_radians().sin_cos();
+        Mat3::from_rows(
+            Vec3::new(c, -s, 0.0),
+            Vec3::new(s, c, 0.0),
+            Vec3::new(0.0, 0.0, 1.0),
+        )
+    }
+
+    /// Rotation by Euler angles in degrees, around the x axis first, then y, then z.
+    pub fn from_euler(angles: Vec3<f64>) -> Self {
+        Mat3::rotation_z(angles.z) * Mat3::rotation_y(angles.y) * Mat3::rotation_x(angles.x)
+    }
+
+    pub fn transpose(&self) -> Self {
+        let [a, b, c] = self.rows;
+        Mat3::from_rows(
+            Vec3::new(a.x, b.x, c.x),
+            Vec3::new(a.y, b.y, c.y),
+            Vec3::new(a.z, b.z, c.z),
+        )
+    }
+}
+
+impl Mul<Vec3<f64>> for Mat3 {
+    type Output = Vec3<f64>;
+
+    #[inline(always)]
+    fn mul(self, v: Vec3<f64>) -> Vec3<f64> {
+        Vec3::new(
+            self.rows[0].dot(v),
+            self.rows[1].dot(v),
+            self.rows[2].dot(v),
+        )
+    }
+}
+
+impl Mul<Mat3> for Mat3 {
+    type Output = Mat3;
+
+    fn mul(self, m: Mat3) -> Mat3 {
+        let t = m.transpose();
+        Mat3::from_rows(t * self.rows[0], t * self.rows[1], t * self.rows[2])
+    }
+}
+
+/// Rotation, then uniform scale, then translation, mapping object space to world space.
+#[derive(Debug, Copy, Clone)]
+pub struct Transform {
+    pub rotation: Mat3,
+    pub scale: f64,
+    pub translation: Vec3<f64>,
+}
+
+impl Default for Transform {
+    fn default() -> Self {
+        Transform {
+            rotation: Mat3::identity(),
+            scale: 1.0,
+            translation: Vec3::zero(),
+        }
+    }
+}
+
+impl Transform {
+    /// Create a transform from Euler angles in degrees, see `Mat3::from_euler`.
+    pub fn new(translation: Vec3<f64>, rotation: Vec3<f64>, scale: f64) -> Self {
+        assert!(scale > 0.0, "Scale should be positive.");
+        Transform {
+            rotation: Mat3::from_euler(rotation),
+            scale,
+            translation,
+        }
+    }
+
+    /// Create a rigid transform placing the object space origin at `origin`, with its y axis
+    /// along `axis`, e.g. for primitives built around an axis.
+    pub fn from_axis(origin: Vec3<f64>, axis: Vec3<f64>) -> Self {
+        let y = axis.unit_vector();
+        let a = if y.x.abs() > 0.9 {
+            Vec3::new(0.0, 1.0, 0.0)
+        } else {
+            Vec3::new(1.0, 0.0, 0.0)
+        };
+        let x = (a - y * a.dot(y)).unit_vector();
+        let z = x.cross(y);
+        Transform {
+            rotation: Mat3::from_rows(x, y, z).transpose(),
+            scale: 1.0,
+            translation: origin,
+        }
+    }
+
+    #[inline(always)]
+    pub fn point(&self, p: Vec3<f64>) -> Vec3<f64> {
+        self.rotation * p * self.scale + self.translation
+    }
+
+    #[inline(always)]
+    pub fn vector(&self, v: Vec3<f64>) -> Vec3<f64> {
+        self.rotation * v * self.scale
+    }
+
+    /// Transform a normal, which stays a unit vector under uniform scaling.
+    #[inline(always)]
+    pub fn normal(&self, n: Vec3<f64>) -> Vec3<f64> {
+        self.rotation * n
+    }
+
+    #[inline(always)]
+    pub fn inverse_point(&self, p: Vec3<f64>) -> Vec3<f64> {
+        self.rotation.transpose() * (p - self.translation) / self.scale
+    }
+
+    #[inline(always)]
+    pub fn inverse_vector(&self, v: Vec3<f64>) -> Vec3<f64> {
+        self.rotation.transpose() * v / self.scale
+    }
+}
+
+/// Unit quaternion representing a rotation.
+#[derive(Debug, Copy, Clone)]
+pub struct Quaternion {
+    pub w: f64,
+    pub v: Vec3<f64>,
+}
+
+impl Quaternion {
+    pub fn identity() -> Self {
+        Quaternion {
+            w: 1.0,
+            v: Vec3::zero(),
+        }
+    }
+
+    /// Rotation by `angle` in degrees around `axis`, counter-clockwise looking down the axis.
+    pub fn from_axis_angle(axis: Vec3<f64>, angle: f64) -> Self {
+        assert!(
+            axis.length_square() > 0.0,
+            "Rotation axis should not be zero."
+        );
+        let (s, c) = (angle.to_radians() / 2.0).sin_cos();
+        Quaternion {
+            w: c,
+            v: axis.unit_vector() * s,
+        }
+    }
+
+    pub fn normalize(&self) -> Self {
+        let len = (self.w * self.w + self.v.length_square()).sqrt();
+        Quaternion {
+            w: self.w / len,
+            v: self.v / len,
+        }
+    }
+
+    pub fn conjugate(&self) -> Self {
+        Quaternion {
+            w: self.w,
+            v: -self.v,
+        }
+    }
+
+    #[inline(always)]
+    pub fn rotate(&self, p: Vec3<f64>) -> Vec3<f64> {
+        let t = self.v.cross(p) * 2.0;
+        p + t * self.w + self.v.cross(t)
+    }
+
+    pub fn to_mat3(&self) -> Mat3 {
+        // the columns are the rotated axes
+        Mat3::from_rows(
+            self.rotate(Vec3::new(1.0, 0.0, 0.0)),
+            self.rotate(Vec3::new(0.0, 1.0, 0.0)),
+            self.rotate(Vec3::new(0.0, 0.0, 1.0)),
+        )
+        .transpose()
+    }
+
+    /// Spherical linear interpolation, along the shorter arc.
+    pub fn slerp(&self, other: &Quaternion, t: f64) -> Self {
+        let mut cos = self.w * other.w + self.v.dot(other.v);
+        let mut other = *other;
+        if cos < 0.0 {
+            cos = -cos;
+            other = Quaternion {
+                w: -other.w,
+                v: -other.v,
+            };
+        }
+        let (a, b) = if cos > 0.9995 {
+            (1.0 - t, t)
+        } else {
+            let theta = cos.acos();
+            let s = theta.sin();
+            (((1.0 - t) * theta).sin() / s, (t * theta).sin() / s)
+        };
+        Quaternion {
+            w: self.w * a + other.w * b,
+            v: self.v * a + other.v * b,
+        }
+        .normalize()
+    }
+}
+
+impl Mul<Quaternion> for Quaternion {
+    type Output = Quaternion;
+
+    /// Compose rotations, `rhs` being applied first.
+    fn mul(self, rhs: Quaternion) -> Quaternion {
+        Quaternion {
+            w: self.w * rhs.w - self.v.dot(rhs.v),
+            v: rhs.v * self.w + self.v * rhs.w + self.v.cross(rhs.v),
+        }
+    }
+}
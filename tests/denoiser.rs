@@ -0,0 +1,52 @@
+//! The denoiser should bring a noisy render of the Cornell box closer to a converged one.
+
+use ray_tracer::config::cornell_box::CornellBoxScene;
+use ray_tracer::config::SceneConfig;
+use ray_tracer::render::denoiser::{AtrousDenoiser, DenoiseParams};
+use ray_tracer::render::filter::Filter;
+use ray_tracer::render::multi_renderer::{MultiRenderer, PresetLevel};
+use ray_tracer::utils::Picture;
+
+/// Render the Cornell box in linear color with `samples` per pixel & the denoiser features.
+fn render_cornell_box(samples: usize) -> MultiRenderer {
+    let scene = CornellBoxScene {};
+    let mut r = MultiRenderer::new();
+    r.set_camera(scene.get_camera());
+    r.set_world(scene.get_world());
+    r.set_render_preset(PresetLevel::Low);
+    r.set_pixel_sample(samples);
+    r.set_gamma_correction(false);
+    r.set_aovs(&DenoiseParams::FEATURES);
+    r
+}
+
+/// Mean squared error against `reference`, on colors clamped to the displayable range.
+fn mse(p: &Picture, reference: &Picture) -> f64 {
+    assert_eq!(p.data.len(), reference.data.len());
+    let sum: f64 = p
+        .data
+        .iter()
+        .zip(&reference.data)
+        .map(|(a, b)| {
+            let d = a.apply(|x| x.min(1.0)) - b.apply(|x| x.min(1.0));
+            d.length_square() as f64
+        })
+        .sum();
+    sum / p.data.len() as f64
+}
+
+#[test]
+fn denoiser_reduces_error_on_cornell_box() {
+    let (reference, _) = render_cornell_box(256).render_with_aovs().unwrap();
+    let (raw, aovs) = render_cornell_box(8).render_with_aovs().unwrap();
+
+    let mut denoised = Picture::new(raw.width, raw.height);
+    denoised.data = raw.data.clone();
+    AtrousDenoiser::from_aovs(DenoiseParams::default(), &aovs)
+        .expect("Denoiser features are not rendered.")
+        .filter(&mut denoised);
+
+    let (raw_error, denoised_error) = (mse(&raw, &reference), mse(&denoised, &reference));
+    println!("MSE raw = {}, denoised = {}", raw_error, denoised_error);
+    assert!(denoised_error < raw_error);
+}
@@ -0,0 +1,37 @@
+//! Rays should hit distance functions whose surfaces touch their bounding boxes.
+
+use std::sync::Arc;
+
+use ray_tracer::object::aabb::AABB;
+use ray_tracer::object::sdf::{make_distance_function, SdfBox, SdfObject};
+use ray_tracer::object::{make_material, Face, Hittable, LambertianDiffuse, SolidColor};
+use ray_tracer::utils::{Ray, Vec3};
+
+#[test]
+fn ray_hits_top_face_on_bounding_box() {
+    let half_size = Vec3::new(0.5, 1.2, 0.5);
+    let sdf = make_distance_function(SdfBox {
+        center: Vec3::zero(),
+        half_size,
+    });
+    let mat = make_material(LambertianDiffuse {
+        texture: Arc::new(SolidColor::new(0.5, 0.5, 0.5)),
+    });
+    let bounding_box = AABB {
+        min: -half_size,
+        max: half_size,
+    };
+    let object = SdfObject::new(&sdf, bounding_box, &mat);
+
+    let r = Ray {
+        orig: Vec3::new(0.1, 5.0, -0.2),
+        dir: Vec3::new(0.0, -1.0, 0.0),
+        t: 0.0,
+    };
+    let h = object
+        .hit(&r, 0.001, f64::INFINITY)
+        .expect("Ray should hit the box.");
+    assert!((h.p.y - 1.2).abs() < 1e-3, "Hit at {}.", h.p);
+    assert!(matches!(h.f, Face::Inward));
+    assert!(h.normal.y > 0.99, "Normal {}.", h.normal);
+}
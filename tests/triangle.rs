@@ -0,0 +1,35 @@
+//! Shading normals of triangles should face against the ray, whatever the vertex normals say.
+
+use std::sync::Arc;
+
+use ray_tracer::object::triangle::Triangle;
+use ray_tracer::object::{make_material, Hittable, LambertianDiffuse, SolidColor};
+use ray_tracer::utils::{Ray, Vec3};
+
+#[test]
+fn vertex_normals_against_winding_face_the_ray() {
+    let mat = make_material(LambertianDiffuse {
+        texture: Arc::new(SolidColor::new(0.5, 0.5, 0.5)),
+    });
+    // wound to face +y, with normals pointing down
+    let mut t = Triangle::new(
+        [
+            Vec3::new(0.0, 0.0, 0.0),
+            Vec3::new(0.0, 0.0, 1.0),
+            Vec3::new(1.0, 0.0, 0.0),
+        ],
+        &mat,
+    );
+    let down = Vec3::new(0.0, -1.0, 0.0);
+    t.set_normals([down, down, down]);
+
+    let r = Ray {
+        orig: Vec3::new(0.2, 1.0, 0.2),
+        dir: Vec3::new(0.0, -1.0, 0.0),
+        t: 0.0,
+    };
+    let h = t
+        .hit(&r, 0.001, f64::INFINITY)
+        .expect("Ray should hit the triangle.");
+    assert!(h.normal.dot(r.dir) < 0.0, "Normal {}.", h.normal);
+}